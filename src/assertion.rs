@@ -0,0 +1,319 @@
+use hyper::http::{self, HeaderName, HeaderValue, StatusCode};
+use std::str::FromStr;
+
+/// A status code or a class of them like `2xx`, specified by `--assert-status`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusPattern {
+    Exact(StatusCode),
+    /// The first digit of the status code
+    Class(u16),
+}
+
+impl StatusPattern {
+    fn matches(&self, status: StatusCode) -> bool {
+        match *self {
+            Self::Exact(expected) => status == expected,
+            Self::Class(class) => status.as_u16() / 100 == class,
+        }
+    }
+}
+
+impl FromStr for StatusPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(class) = s
+            .strip_suffix("xx")
+            .or_else(|| s.strip_suffix("XX"))
+            .and_then(|class| class.parse::<u16>().ok())
+            .filter(|class| (1..=5).contains(class))
+        {
+            return Ok(Self::Class(class));
+        }
+        StatusCode::from_str(s)
+            .map(Self::Exact)
+            .map_err(|err| format!("status must be like 200 or 2xx, but got {s}: {err}"))
+    }
+}
+
+impl std::fmt::Display for StatusPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact(status) => write!(f, "{}", status.as_u16()),
+            Self::Class(class) => write!(f, "{class}xx"),
+        }
+    }
+}
+
+/// A header check specified by `--assert-header` like `name` or `name: value`
+#[derive(Clone, Debug)]
+pub struct HeaderAssertion {
+    pub name: HeaderName,
+    /// None only checks the existence
+    pub value: Option<HeaderValue>,
+}
+
+impl FromStr for HeaderAssertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once(':') {
+            Some((name, value)) => (name, Some(value.trim_start_matches(' '))),
+            None => (s, None),
+        };
+        Ok(Self {
+            name: HeaderName::from_str(name.trim())
+                .map_err(|err| format!("invalid header name {name}: {err}"))?,
+            value: value
+                .map(HeaderValue::from_str)
+                .transpose()
+                .map_err(|err| format!("invalid header value for {name}: {err}"))?,
+        })
+    }
+}
+
+/// A JSON check specified by `--assert-json` like `/status=ok` or `/items/0/id=1`
+#[derive(Clone, Debug)]
+pub struct JsonAssertion {
+    /// JSON pointer (RFC 6901)
+    pub pointer: String,
+    pub value: serde_json::Value,
+}
+
+impl FromStr for JsonAssertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pointer, value) = s
+            .split_once('=')
+            .ok_or("syntax for --assert-json is pointer=value")?;
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(format!(
+                "JSON pointer must be empty or start with '/', but got {pointer}"
+            ));
+        }
+        Ok(Self {
+            pointer: pointer.to_string(),
+            // Bare words are compared as strings
+            value: serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string())),
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AssertionError {
+    #[error("status {0} isn't one of {1}")]
+    Status(u16, String),
+    #[error("body doesn't match /{0}/")]
+    BodyRegex(String),
+    #[error("body doesn't contain {0:?}")]
+    BodyContains(String),
+    #[error("body isn't JSON")]
+    NotJson,
+    #[error("JSON {0:?} isn't {1}")]
+    Json(String, serde_json::Value),
+    #[error("header {0} is missing")]
+    HeaderMissing(HeaderName),
+    #[error("header {0} isn't {1:?}")]
+    Header(HeaderName, HeaderValue),
+    #[error("body is larger than {0} bytes")]
+    BodySize(usize),
+}
+
+/// Checks on responses. A response failing any of them is counted as an error.
+#[derive(Debug, Default)]
+pub struct Assertions {
+    /// Empty allows any status
+    pub status: Vec<StatusPattern>,
+    pub body_regex: Option<regex::bytes::Regex>,
+    pub body_contains: Vec<String>,
+    pub json: Vec<JsonAssertion>,
+    pub headers: Vec<HeaderAssertion>,
+    pub max_body_size: Option<usize>,
+}
+
+impl Assertions {
+    /// Whether the body needs to be kept to check it
+    pub fn need_body(&self) -> bool {
+        self.body_regex.is_some() || !self.body_contains.is_empty() || !self.json.is_empty()
+    }
+
+    /// `body` is None unless `need_body` and `len_bytes` is the decoded size if decoded
+    pub fn check(
+        &self,
+        status: StatusCode,
+        headers: &http::HeaderMap,
+        len_bytes: usize,
+        body: Option<&[u8]>,
+    ) -> Result<(), AssertionError> {
+        if !self.status.is_empty() && !self.status.iter().any(|p| p.matches(status)) {
+            return Err(AssertionError::Status(
+                status.as_u16(),
+                self.status
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ));
+        }
+
+        for header in &self.headers {
+            let mut values = headers.get_all(&header.name).iter().peekable();
+            if values.peek().is_none() {
+                return Err(AssertionError::HeaderMissing(header.name.clone()));
+            }
+            if let Some(expected) = &header.value {
+                if !values.any(|v| v == expected) {
+                    return Err(AssertionError::Header(
+                        header.name.clone(),
+                        expected.clone(),
+                    ));
+                }
+            }
+        }
+
+        if let Some(max_body_size) = self.max_body_size {
+            if len_bytes > max_body_size {
+                return Err(AssertionError::BodySize(max_body_size));
+            }
+        }
+
+        let body = body.unwrap_or_default();
+
+        if let Some(regex) = &self.body_regex {
+            if !regex.is_match(body) {
+                return Err(AssertionError::BodyRegex(regex.to_string()));
+            }
+        }
+
+        for needle in &self.body_contains {
+            if memchr::memmem::find(body, needle.as_bytes()).is_none() {
+                return Err(AssertionError::BodyContains(needle.clone()));
+            }
+        }
+
+        if !self.json.is_empty() {
+            let json: serde_json::Value =
+                serde_json::from_slice(body).map_err(|_| AssertionError::NotJson)?;
+            for assertion in &self.json {
+                if json.pointer(&assertion.pointer) != Some(&assertion.value) {
+                    return Err(AssertionError::Json(
+                        assertion.pointer.clone(),
+                        assertion.value.clone(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let assertions = Assertions {
+            status: vec!["2xx".parse().unwrap(), "404".parse().unwrap()],
+            ..Default::default()
+        };
+        let headers = http::HeaderMap::new();
+        assert!(assertions.check(StatusCode::OK, &headers, 0, None).is_ok());
+        assert!(
+            assertions
+                .check(StatusCode::NO_CONTENT, &headers, 0, None)
+                .is_ok()
+        );
+        assert!(
+            assertions
+                .check(StatusCode::NOT_FOUND, &headers, 0, None)
+                .is_ok()
+        );
+        assert_eq!(
+            assertions
+                .check(StatusCode::INTERNAL_SERVER_ERROR, &headers, 0, None)
+                .unwrap_err()
+                .to_string(),
+            "status 500 isn't one of 2xx,404"
+        );
+        assert!("6xx".parse::<StatusPattern>().is_err());
+        assert!("abc".parse::<StatusPattern>().is_err());
+    }
+
+    #[test]
+    fn test_body() {
+        let assertions = Assertions {
+            body_regex: Some(regex::bytes::Regex::new("^\\{.*\\}$").unwrap()),
+            body_contains: vec!["\"ok\"".to_string()],
+            json: vec![
+                "/status=ok".parse().unwrap(),
+                "/items/1/id=2".parse().unwrap(),
+            ],
+            max_body_size: Some(100),
+            ..Default::default()
+        };
+        assert!(assertions.need_body());
+        let headers = http::HeaderMap::new();
+        let check = |body: &str| {
+            assertions
+                .check(StatusCode::OK, &headers, body.len(), Some(body.as_bytes()))
+                .map_err(|e| e.to_string())
+        };
+
+        assert_eq!(
+            check(r#"{"status":"ok","items":[{"id":1},{"id":2}]}"#),
+            Ok(())
+        );
+        assert_eq!(
+            check("<html>error</html>"),
+            Err("body doesn't match /^\\{.*\\}$/".to_string())
+        );
+        assert_eq!(
+            check(r#"{"status":"error"}"#),
+            Err("body doesn't contain \"\\\"ok\\\"\"".to_string())
+        );
+        assert_eq!(
+            check(r#"{"status":"ok","items":[{"id":1},{"id":"2"}]}"#),
+            Err("JSON \"/items/1/id\" isn't 2".to_string())
+        );
+        assert_eq!(
+            check(&format!(r#"{{"status":"ok","pad":"{}"}}"#, "x".repeat(100))),
+            Err("body is larger than 100 bytes".to_string())
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let assertions = Assertions {
+            headers: vec![
+                "x-request-id".parse().unwrap(),
+                "content-type: application/json".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        assert!(!assertions.need_body());
+        let mut headers = http::HeaderMap::new();
+        headers.insert("content-type", "application/json".parse().unwrap());
+        assert_eq!(
+            assertions
+                .check(StatusCode::OK, &headers, 0, None)
+                .unwrap_err()
+                .to_string(),
+            "header x-request-id is missing"
+        );
+        headers.insert("x-request-id", "1".parse().unwrap());
+        assert!(assertions.check(StatusCode::OK, &headers, 0, None).is_ok());
+        headers.insert("content-type", "text/html".parse().unwrap());
+        assert_eq!(
+            assertions
+                .check(StatusCode::OK, &headers, 0, None)
+                .unwrap_err()
+                .to_string(),
+            "header content-type isn't \"application/json\""
+        );
+    }
+}
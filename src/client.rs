@@ -21,8 +21,13 @@ use url::{ParseError, Url};
 
 use crate::{
     ConnectToEntry,
+    assertion::{AssertionError, Assertions},
     aws_auth::AwsSignatureConfig,
+    connection_stats::{Connection, ConnectionRegistry},
+    counting_stream::CountingStream,
+    decompress::{ContentEncoding, Decoder},
     pcg64si::Pcg64Si,
+    retry::RetryPolicy,
     url_generator::{UrlGenerator, UrlGeneratorError},
 };
 
@@ -31,8 +36,51 @@ type SendRequestHttp2 = hyper::client::conn::http2::SendRequest<Full<Bytes>>;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ConnectionTime {
+    /// When DNS lookup finished
     pub dns_lookup: std::time::Instant,
+    /// When TCP (or unix socket, vsock) connection was established
+    pub tcp_connect: std::time::Instant,
+    /// When the CONNECT tunnel through the proxy was established
+    pub proxy_connect: Option<std::time::Instant>,
+    /// When TLS handshake finished
+    pub tls_handshake: Option<std::time::Instant>,
+    /// When the connection became ready to send requests
     pub dialup: std::time::Instant,
+    /// The IP address connected to. None for unix socket and vsock.
+    pub remote_ip: Option<std::net::IpAddr>,
+    /// Whether the DNS lookup for this connection returned different addresses from the previous one
+    pub dns_changed: bool,
+    /// Whether this connection replaced a kept-alive one which was lost
+    pub reconnect: bool,
+}
+
+impl ConnectionTime {
+    pub(crate) fn new(dns_lookup: Instant, tcp_connect: Instant) -> Self {
+        Self {
+            dns_lookup,
+            tcp_connect,
+            proxy_connect: None,
+            tls_handshake: None,
+            dialup: tcp_connect,
+            remote_ip: None,
+            dns_changed: false,
+            reconnect: false,
+        }
+    }
+
+    pub fn tcp_connect_duration(&self) -> std::time::Duration {
+        self.tcp_connect - self.dns_lookup
+    }
+
+    pub fn proxy_connect_duration(&self) -> Option<std::time::Duration> {
+        self.proxy_connect.map(|t| t - self.tcp_connect)
+    }
+
+    pub fn tls_handshake_duration(&self) -> Option<std::time::Duration> {
+        // TLS with the target starts after the proxy tunnel is established
+        let tls_start = self.proxy_connect.unwrap_or(self.tcp_connect);
+        self.tls_handshake.map(|t| t - tls_start)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,12 +102,72 @@ pub struct RequestResult {
     pub status: http::StatusCode,
     /// Length of body
     pub len_bytes: usize,
+    /// Content-Encoding of the body
+    pub content_encoding: ContentEncoding,
+    /// Length of the decoded body. None unless --decompress is set and the encoding is supported.
+    pub decoded_len_bytes: Option<usize>,
+    /// Values of the `--track-header` headers in the same order. None if the header is missing.
+    pub tracked_headers: Vec<(http::HeaderName, Option<http::HeaderValue>)>,
+    /// The IP address of the connection used. None for unix socket and vsock.
+    pub remote_ip: Option<std::net::IpAddr>,
+    /// Redirects followed to get the response, in order
+    pub redirects: Vec<RedirectHop>,
+    /// Number of failed attempts before this one
+    pub retries: u32,
+    /// When the first attempt started. None unless retried.
+    pub first_attempt_start: Option<std::time::Instant>,
+    /// The URL or the matched `--url-group` pattern of the request. None unless the per-URL statistics are enabled.
+    pub url_group: Option<String>,
+    /// Size of the request in HTTP/1 form: the request line, headers and body
+    pub request_bytes: u64,
+    /// Size of the status line and headers of the response in HTTP/1 form
+    pub response_header_bytes: u64,
+}
+
+/// A redirect response followed by a request
+#[derive(Debug, Clone, Copy)]
+pub struct RedirectHop {
+    pub status: http::StatusCode,
+    /// From sending the request to reading the whole redirect response, including connecting
+    pub duration: std::time::Duration,
 }
 
 impl RequestResult {
-    /// Duration the request takes.
+    /// Duration the request takes, including all the attempts.
     pub fn duration(&self) -> std::time::Duration {
-        self.end - self.start_latency_correction.unwrap_or(self.start)
+        self.end
+            - self
+                .start_latency_correction
+                .or(self.first_attempt_start)
+                .unwrap_or(self.start)
+    }
+
+    /// Duration since the request was actually sent, including all the attempts.
+    /// Same as `duration` unless latency correction is used.
+    pub fn service_time(&self) -> std::time::Duration {
+        self.end - self.first_attempt_start.unwrap_or(self.start)
+    }
+
+    /// How long the request waited to be sent since when it should have been by latency correction.
+    pub fn queueing_delay(&self) -> Option<std::time::Duration> {
+        self.start_latency_correction
+            .map(|start_latency_correction| {
+                self.first_attempt_start
+                    .unwrap_or(self.start)
+                    .saturating_duration_since(start_latency_correction)
+            })
+    }
+
+    /// Time to first byte of the body since the start of the last attempt. None if the body is empty.
+    pub fn ttfb(&self) -> Option<std::time::Duration> {
+        self.first_byte
+            .map(|first_byte| first_byte.saturating_duration_since(self.start))
+    }
+
+    /// Time from the first byte to the end of the body. None if the body is empty.
+    pub fn download_time(&self) -> Option<std::time::Duration> {
+        self.first_byte
+            .map(|first_byte| self.end.saturating_duration_since(first_byte))
     }
 }
 
@@ -74,15 +182,40 @@ pub struct Dns {
     pub connect_to: Vec<ConnectToEntry>,
     pub resolver:
         hickory_resolver::Resolver<hickory_resolver::name_server::TokioConnectionProvider>,
+    /// Sorted addresses of the last lookup for each host to detect DNS changes
+    pub resolved: std::sync::Mutex<std::collections::HashMap<String, Vec<std::net::IpAddr>>>,
+    /// Addresses from `--hosts-file`, which take precedence over the resolver
+    pub hosts: std::collections::HashMap<String, Vec<std::net::IpAddr>>,
+}
+
+/// Parses a hosts file like /etc/hosts into addresses of each lowercased host name
+pub fn parse_hosts(content: &str) -> std::collections::HashMap<String, Vec<std::net::IpAddr>> {
+    let mut hosts = std::collections::HashMap::<_, Vec<_>>::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let Some(Ok(ip)) = fields.next().map(str::parse::<std::net::IpAddr>) else {
+            continue;
+        };
+        for name in fields {
+            let addrs = hosts.entry(name.to_ascii_lowercase()).or_default();
+            if !addrs.contains(&ip) {
+                addrs.push(ip);
+            }
+        }
+    }
+    hosts
 }
 
 impl Dns {
-    /// Perform a DNS lookup for a given url and returns (ip_addr, port)
+    /// Perform a DNS lookup for a given url and returns ((ip_addr, port), whether the resolved addresses have changed since the last lookup).
+    /// Only addresses which can be connected from one of `bind_addresses` are chosen unless it's empty.
     async fn lookup<R: Rng>(
         &self,
         url: &Url,
+        bind_addresses: &[std::net::IpAddr],
         rng: &mut R,
-    ) -> Result<(std::net::IpAddr, u16), ClientError> {
+    ) -> Result<((std::net::IpAddr, u16), bool), ClientError> {
         let host = url.host_str().ok_or(ClientError::HostNotFound)?;
         let port = url
             .port_or_known_default()
@@ -112,17 +245,50 @@ impl Dns {
 
         // Perform actual DNS lookup, either on the original (host, port), or
         // on the (host, port) specified with `--connect-to`.
-        let addrs = self
-            .resolver
-            .lookup_ip(host)
-            .await
-            .map_err(Box::new)?
-            .iter()
-            .collect::<Vec<_>>();
+        let mut addrs = if let Some(addrs) = self.hosts.get(&host.to_ascii_lowercase()) {
+            addrs.clone()
+        } else {
+            self.resolver
+                .lookup_ip(host)
+                .await
+                .map_err(Box::new)?
+                .iter()
+                .collect::<Vec<_>>()
+        };
+
+        let addr = if bind_addresses.is_empty() {
+            *addrs.choose(rng).ok_or(ClientError::DNSNoRecord)?
+        } else {
+            let first = *addrs.first().ok_or(ClientError::DNSNoRecord)?;
+            **addrs
+                .iter()
+                .filter(|addr| {
+                    bind_addresses
+                        .iter()
+                        .any(|local| local.is_ipv4() == addr.is_ipv4())
+                })
+                .collect::<Vec<_>>()
+                .choose(rng)
+                .ok_or(ClientError::NoBindAddress(first))?
+        };
 
-        let addr = *addrs.choose(rng).ok_or(ClientError::DNSNoRecord)?;
+        addrs.sort_unstable();
+        let changed = match self.resolved.lock().unwrap().entry(host.to_string()) {
+            std::collections::hash_map::Entry::Occupied(mut entry) => {
+                if *entry.get() != addrs {
+                    entry.insert(addrs);
+                    true
+                } else {
+                    false
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(addrs);
+                false
+            }
+        };
 
-        Ok((addr, port))
+        Ok(((addr, port), changed))
     }
 }
 
@@ -170,6 +336,10 @@ pub enum ClientError {
     InvalidUri(#[from] http::uri::InvalidUri),
     #[error("timeout")]
     Timeout,
+    #[error("connect timeout")]
+    ConnectTimeout,
+    #[error("No --bind-address has the same address family as {0}")]
+    NoBindAddress(std::net::IpAddr),
     #[error("aborted due to deadline")]
     Deadline,
     #[error(transparent)]
@@ -178,6 +348,149 @@ pub enum ClientError {
     UrlParseError(#[from] ParseError),
     #[error("AWS SigV4 signature error: {0}")]
     SigV4Error(&'static str),
+    #[error("Failed to decode {0} body: {1}")]
+    DecompressError(ContentEncoding, std::io::Error),
+    #[error("Assertion failed: {error}")]
+    AssertionFailed {
+        /// Of the rejected response
+        status: http::StatusCode,
+        /// Latency of the rejected response
+        duration: std::time::Duration,
+        error: AssertionError,
+    },
+    /// The error of the last attempt of a failed request
+    #[error("{error}")]
+    Failed {
+        /// Number of failed attempts before the last one
+        retries: u32,
+        /// From the start of the first attempt to the failure of the last one. None if no attempt was made.
+        duration: Option<std::time::Duration>,
+        /// When the request failed, which may be well before the result is aggregated
+        end: std::time::Instant,
+        error: Box<ClientError>,
+    },
+}
+
+impl ClientError {
+    /// Wraps an error of a request which wasn't attempted, such as by the deadline, to record when it failed
+    pub fn unattempted(self) -> Self {
+        ClientError::Failed {
+            retries: 0,
+            duration: None,
+            end: std::time::Instant::now(),
+            error: Box::new(self),
+        }
+    }
+
+    /// The error of the last attempt
+    pub fn last_attempt(&self) -> &ClientError {
+        match self {
+            ClientError::Failed { error, .. } => error,
+            err => err,
+        }
+    }
+
+    /// Stable classification of the error, independent of addresses or other details in the message
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ClientError::Failed { error, .. } => error.kind(),
+            ClientError::Deadline => ErrorKind::Deadline,
+            ClientError::Timeout | ClientError::ConnectTimeout => ErrorKind::Timeout,
+            ClientError::ResolveError(_) | ClientError::DNSNoRecord => ErrorKind::Dns,
+            #[cfg(feature = "native-tls")]
+            ClientError::NativeTlsError(_) => ErrorKind::Tls,
+            #[cfg(feature = "rustls")]
+            ClientError::RustlsError(_) | ClientError::InvalidDnsName(_) => ErrorKind::Tls,
+            ClientError::IoError(err) => io_error_kind(err),
+            ClientError::HyperError(err) => {
+                let mut source = std::error::Error::source(err);
+                while let Some(err) = source {
+                    if let Some(io) = err.downcast_ref::<std::io::Error>() {
+                        return io_error_kind(io);
+                    }
+                    source = err.source();
+                }
+                if err.is_timeout() {
+                    ErrorKind::Timeout
+                } else if err.is_incomplete_message() || err.is_closed() || err.is_canceled() {
+                    ErrorKind::ConnectionReset
+                } else {
+                    ErrorKind::Protocol
+                }
+            }
+            ClientError::TooManyRedirect | ClientError::DecompressError(..) => ErrorKind::Protocol,
+            ClientError::AssertionFailed { .. } => ErrorKind::Validation,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+fn io_error_kind(err: &std::io::Error) -> ErrorKind {
+    #[cfg(feature = "rustls")]
+    if err
+        .get_ref()
+        .is_some_and(|inner| inner.downcast_ref::<rustls::Error>().is_some())
+    {
+        return ErrorKind::Tls;
+    }
+    #[cfg(feature = "native-tls")]
+    if err
+        .get_ref()
+        .is_some_and(|inner| inner.downcast_ref::<native_tls::Error>().is_some())
+    {
+        return ErrorKind::Tls;
+    }
+    match err.kind() {
+        std::io::ErrorKind::ConnectionRefused => ErrorKind::ConnectRefused,
+        std::io::ErrorKind::ConnectionReset
+        | std::io::ErrorKind::ConnectionAborted
+        | std::io::ErrorKind::BrokenPipe
+        | std::io::ErrorKind::UnexpectedEof => ErrorKind::ConnectionReset,
+        std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+        _ => ErrorKind::Other,
+    }
+}
+
+/// Classification of [`ClientError`] used to aggregate failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    ConnectRefused,
+    ConnectionReset,
+    Tls,
+    Timeout,
+    Dns,
+    Protocol,
+    Deadline,
+    /// A response failed an assertion
+    Validation,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::ConnectRefused => "connect_refused",
+            ErrorKind::ConnectionReset => "connection_reset",
+            ErrorKind::Tls => "tls",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Dns => "dns",
+            ErrorKind::Protocol => "protocol",
+            ErrorKind::Deadline => "deadline",
+            ErrorKind::Validation => "validation",
+            ErrorKind::Other => "other",
+        }
+    }
+}
+
+/// A response read to the end
+struct ReadResponse {
+    status: http::StatusCode,
+    headers: http::HeaderMap,
+    len_bytes: usize,
+    content_encoding: ContentEncoding,
+    decoded_len_bytes: Option<usize>,
+    /// Decoded if possible. None unless assertions need it.
+    body: Option<Vec<u8>>,
 }
 
 pub struct Client {
@@ -190,9 +503,26 @@ pub struct Client {
     pub body: Option<&'static [u8]>,
     pub dns: Dns,
     pub timeout: Option<std::time::Duration>,
+    /// Timeout for establishing a connection, including TLS handshake and proxy CONNECT
+    pub connect_timeout: std::time::Duration,
+    /// Local addresses to bind TCP sockets to. Empty means the OS decides.
+    pub bind_addresses: Vec<std::net::IpAddr>,
     pub redirect_limit: usize,
     pub disable_keepalive: bool,
+    /// Decode compressed response bodies
+    pub decompress: bool,
+    pub assertions: Assertions,
+    /// Response headers to record the value of
+    pub track_headers: Vec<http::HeaderName>,
+    /// Patterns to group URLs for the per-URL statistics. URLs matching none are grouped by themselves.
+    /// None disables the per-URL statistics.
+    pub url_groups: Option<Vec<regex::Regex>>,
+    pub retry: RetryPolicy,
+    /// Stats of the connections opened
+    pub connections: Arc<ConnectionRegistry>,
     pub proxy_url: Option<Url>,
+    /// Overrides the server name for TLS (SNI and certificate verification)
+    pub sni: Option<String>,
     pub aws_config: Option<AwsSignatureConfig>,
     #[cfg(unix)]
     pub unix_socket: Option<std::path::PathBuf>,
@@ -218,20 +548,43 @@ impl Default for Client {
             dns: Dns {
                 resolver: hickory_resolver::Resolver::builder_tokio().unwrap().build(),
                 connect_to: Vec::new(),
+                resolved: Default::default(),
+                hosts: Default::default(),
             },
             timeout: None,
+            connect_timeout: std::time::Duration::from_secs(5),
+            bind_addresses: Vec::new(),
             redirect_limit: 0,
             disable_keepalive: false,
+            decompress: false,
+            assertions: Assertions::default(),
+            track_headers: Vec::new(),
+            url_groups: None,
+            retry: RetryPolicy::default(),
+            connections: Default::default(),
             proxy_url: None,
+            sni: None,
             aws_config: None,
             #[cfg(unix)]
             unix_socket: None,
             #[cfg(feature = "vsock")]
             vsock_addr: None,
             #[cfg(feature = "rustls")]
-            rustls_configs: crate::tls_config::RuslsConfigs::new(false, None, None),
+            rustls_configs: crate::tls_config::RuslsConfigs::new(
+                false,
+                None,
+                None,
+                &Default::default(),
+            )
+            .unwrap(),
             #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
-            native_tls_connectors: crate::tls_config::NativeTlsConnectors::new(false, None, None),
+            native_tls_connectors: crate::tls_config::NativeTlsConnectors::new(
+                false,
+                None,
+                None,
+                &Default::default(),
+            )
+            .unwrap(),
         }
     }
 }
@@ -239,6 +592,9 @@ impl Default for Client {
 struct ClientStateHttp1 {
     rng: Pcg64Si,
     send_request: Option<SendRequestHttp1>,
+    remote_ip: Option<std::net::IpAddr>,
+    /// Of `send_request`
+    connection: Option<Arc<Connection>>,
 }
 
 impl Default for ClientStateHttp1 {
@@ -246,13 +602,54 @@ impl Default for ClientStateHttp1 {
         Self {
             rng: SeedableRng::from_os_rng(),
             send_request: None,
+            remote_ip: None,
+            connection: None,
         }
     }
 }
 
+/// An HTTP/2 connection of the parallel requests on it
+#[derive(Clone)]
+struct Http2Connection {
+    send_request: SendRequestHttp2,
+    /// Of `send_request`
+    connection_time: ConnectionTime,
+    /// Of `send_request`
+    connection: Arc<Connection>,
+    /// Incremented each time a retry replaces the connection
+    generation: u64,
+}
+
 struct ClientStateHttp2 {
     rng: Pcg64Si,
-    send_request: SendRequestHttp2,
+    /// `shared` as of when it was last read
+    http2: Http2Connection,
+    /// Shared by the parallel requests. A retry after a connection error replaces it once for all of them.
+    shared: Arc<tokio::sync::Mutex<Http2Connection>>,
+}
+
+impl ClientStateHttp2 {
+    /// `http2` is the first connection of `shared`
+    fn new(http2: &Http2Connection, shared: &Arc<tokio::sync::Mutex<Http2Connection>>) -> Self {
+        Self {
+            rng: SeedableRng::from_os_rng(),
+            http2: http2.clone(),
+            shared: shared.clone(),
+        }
+    }
+
+    /// Replaces the failed connection unless another request on it already did, and takes the new one
+    async fn reconnect(&mut self, client: &Client) -> Result<(), ClientError> {
+        let mut shared = self.shared.lock().await;
+        if shared.generation == self.http2.generation {
+            *shared = Http2Connection {
+                generation: shared.generation + 1,
+                ..setup_http2(client, true).await?
+            };
+        }
+        self.http2 = shared.clone();
+        Ok(())
+    }
 }
 
 pub enum QueryLimit {
@@ -260,19 +657,24 @@ pub enum QueryLimit {
     Burst(std::time::Duration, usize),
 }
 
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+type TlsStream<S> = tokio_native_tls::TlsStream<S>;
+#[cfg(feature = "rustls")]
+type TlsStream<S> = crate::tls_stream::TlsStream<S>;
+
 // To avoid dynamic dispatch
 // I'm not sure how much this is effective
 pub(crate) enum Stream {
-    Tcp(TcpStream),
-    #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
-    Tls(tokio_native_tls::TlsStream<TcpStream>),
-    #[cfg(feature = "rustls")]
-    // Box for large variant
-    Tls(Box<tokio_rustls::client::TlsStream<TcpStream>>),
+    Tcp(CountingStream<TcpStream>),
+    Tls(TlsStream<CountingStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(CountingStream<tokio::net::UnixStream>),
     #[cfg(unix)]
-    Unix(tokio::net::UnixStream),
+    UnixTls(TlsStream<CountingStream<tokio::net::UnixStream>>),
+    #[cfg(feature = "vsock")]
+    Vsock(CountingStream<tokio_vsock::VsockStream>),
     #[cfg(feature = "vsock")]
-    Vsock(tokio_vsock::VsockStream),
+    VsockTls(TlsStream<CountingStream<tokio_vsock::VsockStream>>),
 }
 
 impl Stream {
@@ -309,6 +711,17 @@ impl Stream {
                 }
                 Ok(send_request)
             }
+            #[cfg(unix)]
+            Stream::UnixTls(stream) => {
+                let (send_request, conn) =
+                    hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+                if with_upgrade {
+                    tokio::spawn(conn.with_upgrades());
+                } else {
+                    tokio::spawn(conn);
+                }
+                Ok(send_request)
+            }
             #[cfg(feature = "vsock")]
             Stream::Vsock(stream) => {
                 let (send_request, conn) =
@@ -320,6 +733,17 @@ impl Stream {
                 }
                 Ok(send_request)
             }
+            #[cfg(feature = "vsock")]
+            Stream::VsockTls(stream) => {
+                let (send_request, conn) =
+                    hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+                if with_upgrade {
+                    tokio::spawn(conn.with_upgrades());
+                } else {
+                    tokio::spawn(conn);
+                }
+                Ok(send_request)
+            }
         }
     }
     async fn handshake_http2(self) -> Result<SendRequestHttp2, ClientError> {
@@ -346,12 +770,24 @@ impl Stream {
                 tokio::spawn(conn);
                 Ok(send_request)
             }
+            #[cfg(unix)]
+            Stream::UnixTls(stream) => {
+                let (send_request, conn) = builder.handshake(TokioIo::new(stream)).await?;
+                tokio::spawn(conn);
+                Ok(send_request)
+            }
             #[cfg(feature = "vsock")]
             Stream::Vsock(stream) => {
                 let (send_request, conn) = builder.handshake(TokioIo::new(stream)).await?;
                 tokio::spawn(conn);
                 Ok(send_request)
             }
+            #[cfg(feature = "vsock")]
+            Stream::VsockTls(stream) => {
+                let (send_request, conn) = builder.handshake(TokioIo::new(stream)).await?;
+                tokio::spawn(conn);
+                Ok(send_request)
+            }
         }
     }
 }
@@ -414,94 +850,196 @@ impl Client {
         let url = self.url_generator.generate(&mut rng)?;
 
         // It automatically caches the result
-        self.dns.lookup(&url, &mut rng).await?;
+        self.dns
+            .lookup(&url, &self.bind_addresses, &mut rng)
+            .await?;
         Ok(())
     }
 
-    pub fn generate_url(&self, rng: &mut Pcg64Si) -> Result<(Cow<Url>, Pcg64Si), ClientError> {
+    pub fn generate_url(&self, rng: &mut Pcg64Si) -> Result<(Cow<'_, Url>, Pcg64Si), ClientError> {
         let snapshot = *rng;
         Ok((self.url_generator.generate(rng)?, snapshot))
     }
 
     /**
      * Returns a stream of the underlying transport. NOT a HTTP client
+     * Bytes sent and received on the socket are counted into `connection`.
      */
     pub(crate) async fn client<R: Rng>(
         &self,
         url: &Url,
         rng: &mut R,
         http_version: http::Version,
-    ) -> Result<(Instant, Stream), ClientError> {
-        // TODO: Allow the connect timeout to be configured
-        let timeout_duration = tokio::time::Duration::from_secs(5);
-
-        if url.scheme() == "https" {
-            let addr = self.dns.lookup(url, rng).await?;
-            let dns_lookup = Instant::now();
-            // If we do not put a timeout here then the connections attempts will
-            // linger long past the configured timeout
-            let stream =
-                tokio::time::timeout(timeout_duration, self.tls_client(addr, url, http_version))
-                    .await;
-            return match stream {
-                Ok(Ok(stream)) => Ok((dns_lookup, stream)),
-                Ok(Err(err)) => Err(err),
-                Err(_) => Err(ClientError::Timeout),
-            };
-        }
+        connection: &Arc<Connection>,
+    ) -> Result<(ConnectionTime, Stream), ClientError> {
         #[cfg(unix)]
         if let Some(socket_path) = &self.unix_socket {
             let dns_lookup = Instant::now();
-            let stream = tokio::time::timeout(
-                timeout_duration,
-                tokio::net::UnixStream::connect(socket_path),
-            )
-            .await;
-            return match stream {
-                Ok(Ok(stream)) => Ok((dns_lookup, Stream::Unix(stream))),
-                Ok(Err(err)) => Err(ClientError::IoError(err)),
-                Err(_) => Err(ClientError::Timeout),
-            };
+            return self
+                .with_connect_timeout(dns_lookup, async {
+                    let stream = CountingStream::new(
+                        tokio::net::UnixStream::connect(socket_path).await?,
+                        connection.clone(),
+                    );
+                    let mut connection_time = ConnectionTime::new(dns_lookup, Instant::now());
+                    if url.scheme() != "https" {
+                        return Ok((connection_time, Stream::Unix(stream)));
+                    }
+                    let stream = self
+                        .connect_tls(stream, url, http_version, connection)
+                        .await?;
+                    connection_time.tls_handshake = Some(Instant::now());
+                    Ok((connection_time, Stream::UnixTls(stream)))
+                })
+                .await;
         }
         #[cfg(feature = "vsock")]
         if let Some(addr) = self.vsock_addr {
             let dns_lookup = Instant::now();
-            let stream =
-                tokio::time::timeout(timeout_duration, tokio_vsock::VsockStream::connect(addr))
-                    .await;
-            return match stream {
-                Ok(Ok(stream)) => Ok((dns_lookup, Stream::Vsock(stream))),
-                Ok(Err(err)) => Err(ClientError::IoError(err)),
-                Err(_) => Err(ClientError::Timeout),
-            };
+            return self
+                .with_connect_timeout(dns_lookup, async {
+                    let stream = CountingStream::new(
+                        tokio_vsock::VsockStream::connect(addr).await?,
+                        connection.clone(),
+                    );
+                    let mut connection_time = ConnectionTime::new(dns_lookup, Instant::now());
+                    if url.scheme() != "https" {
+                        return Ok((connection_time, Stream::Vsock(stream)));
+                    }
+                    let stream = self
+                        .connect_tls(stream, url, http_version, connection)
+                        .await?;
+                    connection_time.tls_handshake = Some(Instant::now());
+                    Ok((connection_time, Stream::VsockTls(stream)))
+                })
+                .await;
+        }
+        if url.scheme() == "https" {
+            let (addr, dns_changed) = self.dns.lookup(url, &self.bind_addresses, rng).await?;
+            let dns_lookup = Instant::now();
+            let bind_address = self.bind_address(addr.0, rng)?;
+            let (tcp_connect, stream) = self
+                .with_connect_timeout(
+                    dns_lookup,
+                    self.tls_client(addr, bind_address, url, http_version, connection),
+                )
+                .await?;
+            let mut connection_time = ConnectionTime::new(dns_lookup, tcp_connect);
+            connection_time.remote_ip = Some(addr.0);
+            connection_time.dns_changed = dns_changed;
+            connection_time.tls_handshake = Some(Instant::now());
+            connection_time.dialup = Instant::now();
+            return Ok((connection_time, stream));
         }
         // HTTP
-        let addr = self.dns.lookup(url, rng).await?;
+        let (addr, dns_changed) = self.dns.lookup(url, &self.bind_addresses, rng).await?;
         let dns_lookup = Instant::now();
-        let stream =
-            tokio::time::timeout(timeout_duration, tokio::net::TcpStream::connect(addr)).await;
-        match stream {
-            Ok(Ok(stream)) => {
-                stream.set_nodelay(true)?;
-                Ok((dns_lookup, Stream::Tcp(stream)))
-            }
-            Ok(Err(err)) => Err(ClientError::IoError(err)),
-            Err(_) => Err(ClientError::Timeout),
+        let bind_address = self.bind_address(addr.0, rng)?;
+        let stream = self
+            .with_connect_timeout(dns_lookup, tcp_connect(addr, bind_address))
+            .await?;
+        let mut connection_time = ConnectionTime::new(dns_lookup, Instant::now());
+        connection_time.remote_ip = Some(addr.0);
+        connection_time.dns_changed = dns_changed;
+        stream.set_nodelay(true)?;
+        Ok((
+            connection_time,
+            Stream::Tcp(CountingStream::new(stream, connection.clone())),
+        ))
+    }
+
+    /// Chooses a local address from `--bind-address` randomly to spread connections across them
+    fn bind_address<R: Rng>(
+        &self,
+        remote: std::net::IpAddr,
+        rng: &mut R,
+    ) -> Result<Option<std::net::IpAddr>, ClientError> {
+        if self.bind_addresses.is_empty() {
+            return Ok(None);
         }
+        self.bind_addresses
+            .iter()
+            .filter(|local| local.is_ipv4() == remote.is_ipv4())
+            .collect::<Vec<_>>()
+            .choose(rng)
+            .map(|&&local| Some(local))
+            .ok_or(ClientError::NoBindAddress(remote))
+    }
+
+    /// Fails with `ClientError::ConnectTimeout` when `fut` doesn't finish within `connect_timeout` since DNS lookup finished.
+    /// Every step of establishing a connection (TCP, TLS and proxy CONNECT) shares the same deadline.
+    async fn with_connect_timeout<T>(
+        &self,
+        dns_lookup: Instant,
+        fut: impl std::future::Future<Output = Result<T, ClientError>>,
+    ) -> Result<T, ClientError> {
+        // If we do not put a timeout here then the connections attempts will
+        // linger long past the configured timeout
+        let deadline = tokio::time::Instant::from_std(dns_lookup + self.connect_timeout);
+        tokio::time::timeout_at(deadline, fut)
+            .await
+            .unwrap_or(Err(ClientError::ConnectTimeout))
+    }
+
+    /// Establishes a CONNECT tunnel to `url` through the proxy
+    async fn proxy_connect(
+        &self,
+        stream: Stream,
+        url: &Url,
+    ) -> Result<hyper::upgrade::Upgraded, ClientError> {
+        let req = {
+            let mut builder = http::Request::builder()
+                .method(Method::CONNECT)
+                .uri(format!(
+                    "{}:{}",
+                    url.host_str().unwrap(),
+                    url.port_or_known_default().unwrap()
+                ));
+            *builder
+                .headers_mut()
+                .ok_or(ClientError::GetHeaderFromBuilderError)? = self.proxy_headers.clone();
+            builder.body(http_body_util::Full::default())?
+        };
+        let res = if self.proxy_http_version == http::Version::HTTP_2 {
+            let mut send_request = stream.handshake_http2().await?;
+            send_request.send_request(req).await?
+        } else {
+            let mut send_request = stream.handshake_http1(true).await?;
+            send_request.send_request(req).await?
+        };
+        Ok(hyper::upgrade::on(res).await?)
     }
 
     async fn tls_client(
         &self,
         addr: (std::net::IpAddr, u16),
+        bind_address: Option<std::net::IpAddr>,
         url: &Url,
         http_version: http::Version,
-    ) -> Result<Stream, ClientError> {
-        let stream = tokio::net::TcpStream::connect(addr).await?;
+        connection: &Arc<Connection>,
+    ) -> Result<(Instant, Stream), ClientError> {
+        let stream = tcp_connect(addr, bind_address).await?;
+        let tcp_connect = Instant::now();
         stream.set_nodelay(true)?;
 
-        let stream = self.connect_tls(stream, url, http_version).await?;
+        let stream = self
+            .connect_tls(
+                CountingStream::new(stream, connection.clone()),
+                url,
+                http_version,
+                connection,
+            )
+            .await?;
 
-        Ok(Stream::Tls(stream))
+        Ok((tcp_connect, Stream::Tls(stream)))
+    }
+
+    /// Server name for SNI and certificate verification
+    fn tls_server_name<'a>(&'a self, url: &'a Url) -> Result<&'a str, ClientError> {
+        match &self.sni {
+            Some(sni) => Ok(sni.as_str()),
+            None => url.host_str().ok_or(ClientError::HostNotFound),
+        }
     }
 
     #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
@@ -510,7 +1048,8 @@ impl Client {
         stream: S,
         url: &Url,
         http_version: http::Version,
-    ) -> Result<tokio_native_tls::TlsStream<S>, ClientError>
+        _connection: &Arc<Connection>,
+    ) -> Result<TlsStream<S>, ClientError>
     where
         S: AsyncRead + AsyncWrite + Unpin,
     {
@@ -518,7 +1057,7 @@ impl Client {
             .native_tls_connectors
             .connector(http_version >= http::Version::HTTP_2);
         let stream = connector
-            .connect(url.host_str().ok_or(ClientError::HostNotFound)?, stream)
+            .connect(self.tls_server_name(url)?, stream)
             .await?;
 
         Ok(stream)
@@ -530,76 +1069,83 @@ impl Client {
         stream: S,
         url: &Url,
         http_version: http::Version,
-    ) -> Result<Box<tokio_rustls::client::TlsStream<S>>, ClientError>
+        connection: &Arc<Connection>,
+    ) -> Result<TlsStream<S>, ClientError>
     where
         S: AsyncRead + AsyncWrite + Unpin,
     {
         let connector =
-            tokio_rustls::TlsConnector::from(self.rustls_configs.config(http_version).clone());
-        let domain = rustls_pki_types::ServerName::try_from(
-            url.host_str().ok_or(ClientError::HostNotFound)?,
-        )?;
+            tokio_rustls::TlsConnector::from(self.rustls_configs.config(http_version).clone())
+                .early_data(self.rustls_configs.early_data());
+        let domain = rustls_pki_types::ServerName::try_from(self.tls_server_name(url)?)?;
         let stream = connector.connect(domain.to_owned(), stream).await?;
 
-        Ok(Box::new(stream))
+        Ok(TlsStream::new(stream, connection.clone()))
     }
 
     async fn client_http1<R: Rng>(
         &self,
         url: &Url,
         rng: &mut R,
-    ) -> Result<(Instant, SendRequestHttp1), ClientError> {
+        connection: &Arc<Connection>,
+    ) -> Result<(ConnectionTime, SendRequestHttp1), ClientError> {
         if let Some(proxy_url) = &self.proxy_url {
             let http_proxy_version = if self.is_proxy_http2() {
                 http::Version::HTTP_2
             } else {
                 http::Version::HTTP_11
             };
-            let (dns_lookup, stream) = self.client(proxy_url, rng, http_proxy_version).await?;
+            let (mut connection_time, stream) = self
+                .client(proxy_url, rng, http_proxy_version, connection)
+                .await?;
             if url.scheme() == "https" {
-                // Do CONNECT request to proxy
-                let req = {
-                    let mut builder =
-                        http::Request::builder()
-                            .method(Method::CONNECT)
-                            .uri(format!(
-                                "{}:{}",
-                                url.host_str().unwrap(),
-                                url.port_or_known_default().unwrap()
-                            ));
-                    *builder
-                        .headers_mut()
-                        .ok_or(ClientError::GetHeaderFromBuilderError)? =
-                        self.proxy_headers.clone();
-                    builder.body(http_body_util::Full::default())?
-                };
-                let res = if self.proxy_http_version == http::Version::HTTP_2 {
-                    let mut send_request = stream.handshake_http2().await?;
-                    send_request.send_request(req).await?
-                } else {
-                    let mut send_request = stream.handshake_http1(true).await?;
-                    send_request.send_request(req).await?
-                };
-                let stream = hyper::upgrade::on(res).await?;
                 let stream = self
-                    .connect_tls(TokioIo::new(stream), url, self.http_version)
+                    .with_connect_timeout(
+                        connection_time.dns_lookup,
+                        self.proxy_connect(stream, url),
+                    )
+                    .await?;
+                connection_time.proxy_connect = Some(Instant::now());
+                let stream = self
+                    .with_connect_timeout(
+                        connection_time.dns_lookup,
+                        self.connect_tls(TokioIo::new(stream), url, self.http_version, connection),
+                    )
                     .await?;
+                connection_time.tls_handshake = Some(Instant::now());
                 let (send_request, conn) =
                     hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
                 tokio::spawn(conn);
-                Ok((dns_lookup, send_request))
+                connection_time.dialup = Instant::now();
+                Ok((connection_time, send_request))
             } else {
                 // Send full URL in request() for HTTP proxy
-                Ok((dns_lookup, stream.handshake_http1(false).await?))
+                let send_request = stream.handshake_http1(false).await?;
+                connection_time.dialup = Instant::now();
+                Ok((connection_time, send_request))
             }
         } else {
-            let (dns_lookup, stream) = self.client(url, rng, http::Version::HTTP_11).await?;
-            Ok((dns_lookup, stream.handshake_http1(false).await?))
+            let (mut connection_time, stream) = self
+                .client(url, rng, http::Version::HTTP_11, connection)
+                .await?;
+            let send_request = stream.handshake_http1(false).await?;
+            connection_time.dialup = Instant::now();
+            Ok((connection_time, send_request))
         }
     }
 
     #[inline]
     pub(crate) fn request(&self, url: &Url) -> Result<http::Request<Full<Bytes>>, ClientError> {
+        self.request_with(url, &self.method, self.body)
+    }
+
+    /// Builds a request with the method and body, which may differ from the configured ones for redirects
+    fn request_with(
+        &self,
+        url: &Url,
+        method: &http::Method,
+        body: Option<&'static [u8]>,
+    ) -> Result<http::Request<Full<Bytes>>, ClientError> {
         let use_proxy = self.proxy_url.is_some() && url.scheme() == "http";
 
         let mut builder = http::Request::builder()
@@ -608,14 +1154,14 @@ impl Client {
             } else {
                 &url[url::Position::BeforePath..]
             })
-            .method(self.method.clone())
+            .method(method.clone())
             .version(if use_proxy {
                 self.proxy_http_version
             } else {
                 self.http_version
             });
 
-        let bytes = self.body.map(Bytes::from_static);
+        let bytes = body.map(Bytes::from_static);
 
         let body = if let Some(body) = &bytes {
             Full::new(body.clone())
@@ -624,10 +1170,14 @@ impl Client {
         };
 
         let mut headers = self.headers.clone();
+        if bytes.is_none() && self.body.is_some() {
+            // The body is dropped for a redirect
+            headers.remove(http::header::CONTENT_TYPE);
+        }
 
         // Apply AWS SigV4 if configured
         if let Some(aws_config) = &self.aws_config {
-            aws_config.sign_request(self.method.as_str(), &mut headers, url, bytes)?
+            aws_config.sign_request(method.as_str(), &mut headers, url, bytes)?
         }
 
         if use_proxy {
@@ -648,6 +1198,22 @@ impl Client {
     async fn work_http1(
         &self,
         client_state: &mut ClientStateHttp1,
+    ) -> Result<RequestResult, ClientError> {
+        let first_attempt_start = std::time::Instant::now();
+        let mut retries = 0;
+        loop {
+            let res = self.work_http1_attempt(client_state).await;
+            if !self.retry.should_retry(&res, retries) {
+                return with_attempts(res, retries, first_attempt_start);
+            }
+            retries += 1;
+            tokio::time::sleep(self.retry.backoff(retries)).await;
+        }
+    }
+
+    async fn work_http1_attempt(
+        &self,
+        client_state: &mut ClientStateHttp1,
     ) -> Result<RequestResult, ClientError> {
         let do_req = async {
             let (url, rng) = self.generate_url(&mut client_state.rng)?;
@@ -658,72 +1224,98 @@ impl Client {
             let mut send_request = if let Some(send_request) = client_state.send_request.take() {
                 send_request
             } else {
-                let (dns_lookup, send_request) =
-                    self.client_http1(&url, &mut client_state.rng).await?;
-                let dialup = std::time::Instant::now();
-
-                connection_time = Some(ConnectionTime { dns_lookup, dialup });
+                let connection = self.connections.open();
+                let (connection_time_, send_request) = self
+                    .client_http1(&url, &mut client_state.rng, &connection)
+                    .await?;
+                connection.established(&connection_time_, false);
+                client_state.remote_ip = connection_time_.remote_ip;
+                client_state.connection = Some(connection);
+                connection_time = Some(connection_time_);
                 send_request
             };
             while send_request.ready().await.is_err() {
                 // This gets hit when the connection for HTTP/1.1 faults
                 // This re-connects
                 start = std::time::Instant::now();
-                let (dns_lookup, send_request_) =
-                    self.client_http1(&url, &mut client_state.rng).await?;
+                let connection = self.connections.open();
+                let (mut connection_time_, send_request_) = self
+                    .client_http1(&url, &mut client_state.rng, &connection)
+                    .await?;
+                connection_time_.reconnect = true;
+                connection.established(&connection_time_, false);
                 send_request = send_request_;
-                let dialup = std::time::Instant::now();
-                connection_time = Some(ConnectionTime { dns_lookup, dialup });
+                client_state.remote_ip = connection_time_.remote_ip;
+                client_state.connection = Some(connection);
+                connection_time = Some(connection_time_);
             }
             let request = self.request(&url)?;
+            let request_bytes = request_size(&request);
             match send_request.send_request(request).await {
                 Ok(res) => {
                     let (parts, mut stream) = res.into_parts();
-                    let mut status = parts.status;
 
-                    let mut len_bytes = 0;
-                    while let Some(chunk) = stream.frame().await {
-                        if first_byte.is_none() {
-                            first_byte = Some(std::time::Instant::now())
-                        }
-                        len_bytes += chunk?.data_ref().map(|d| d.len()).unwrap_or_default();
-                    }
+                    let mut response = self.read_body(parts, &mut stream, &mut first_byte).await?;
 
+                    let mut redirects = Vec::new();
                     if self.redirect_limit != 0 {
-                        if let Some(location) = parts.headers.get("Location") {
-                            let (send_request_redirect, new_status, len) = self
+                        if let Some(location) = response.headers.get("Location").cloned() {
+                            redirects.push(RedirectHop {
+                                status: response.status,
+                                duration: start.elapsed(),
+                            });
+                            let (method, body) =
+                                redirect_method(response.status, &self.method, self.body);
+                            let (send_request_redirect, redirected) = self
                                 .redirect(
                                     send_request,
                                     &url,
-                                    location,
+                                    &location,
+                                    &method,
+                                    body,
                                     self.redirect_limit,
+                                    &mut redirects,
                                     &mut client_state.rng,
                                 )
                                 .await?;
 
                             send_request = send_request_redirect;
-                            status = new_status;
-                            len_bytes = len;
+                            response = redirected;
                         }
                     }
 
                     let end = std::time::Instant::now();
-
                     let result = RequestResult {
                         rng,
                         start_latency_correction: None,
                         start,
                         first_byte,
                         end,
-                        status,
-                        len_bytes,
+                        status: response.status,
+                        len_bytes: response.len_bytes,
+                        content_encoding: response.content_encoding,
+                        decoded_len_bytes: response.decoded_len_bytes,
+                        tracked_headers: self.tracked_headers(&response),
+                        url_group: self.url_group(&url),
                         connection_time,
+                        redirects,
+                        retries: 0,
+                        first_attempt_start: None,
+                        remote_ip: client_state.remote_ip,
+                        request_bytes,
+                        response_header_bytes: response_header_size(&response),
                     };
 
                     if !self.disable_keepalive {
                         client_state.send_request = Some(send_request);
                     }
 
+                    // Served whether or not the response passes the assertions
+                    if let Some(connection) = &client_state.connection {
+                        connection.served(end);
+                    }
+                    self.check_response(&response, &result)?;
+
                     Ok::<_, ClientError>(result)
                 }
                 Err(e) => {
@@ -751,6 +1343,7 @@ impl Client {
         &self,
         url: &Url,
         rng: &mut R,
+        connection: &Arc<Connection>,
     ) -> Result<(ConnectionTime, SendRequestHttp2), ClientError> {
         if let Some(proxy_url) = &self.proxy_url {
             let http_proxy_version = if self.is_proxy_http2() {
@@ -758,34 +1351,29 @@ impl Client {
             } else {
                 http::Version::HTTP_11
             };
-            let (dns_lookup, stream) = self.client(proxy_url, rng, http_proxy_version).await?;
+            let (mut connection_time, stream) = self
+                .client(proxy_url, rng, http_proxy_version, connection)
+                .await?;
             if url.scheme() == "https" {
-                let req = {
-                    let mut builder =
-                        http::Request::builder()
-                            .method(Method::CONNECT)
-                            .uri(format!(
-                                "{}:{}",
-                                url.host_str().unwrap(),
-                                url.port_or_known_default().unwrap()
-                            ));
-                    *builder
-                        .headers_mut()
-                        .ok_or(ClientError::GetHeaderFromBuilderError)? =
-                        self.proxy_headers.clone();
-                    builder.body(http_body_util::Full::default())?
-                };
-                let res = if self.proxy_http_version == http::Version::HTTP_2 {
-                    let mut send_request = stream.handshake_http2().await?;
-                    send_request.send_request(req).await?
-                } else {
-                    let mut send_request = stream.handshake_http1(true).await?;
-                    send_request.send_request(req).await?
-                };
-                let stream = hyper::upgrade::on(res).await?;
                 let stream = self
-                    .connect_tls(TokioIo::new(stream), url, http::Version::HTTP_2)
+                    .with_connect_timeout(
+                        connection_time.dns_lookup,
+                        self.proxy_connect(stream, url),
+                    )
+                    .await?;
+                connection_time.proxy_connect = Some(Instant::now());
+                let stream = self
+                    .with_connect_timeout(
+                        connection_time.dns_lookup,
+                        self.connect_tls(
+                            TokioIo::new(stream),
+                            url,
+                            http::Version::HTTP_2,
+                            connection,
+                        ),
+                    )
                     .await?;
+                connection_time.tls_handshake = Some(Instant::now());
                 let (send_request, conn) =
                     hyper::client::conn::http2::Builder::new(TokioExecutor::new())
                         // from nghttp2's default
@@ -794,25 +1382,51 @@ impl Client {
                         .handshake(TokioIo::new(stream))
                         .await?;
                 tokio::spawn(conn);
-                let dialup = std::time::Instant::now();
-
-                Ok((ConnectionTime { dns_lookup, dialup }, send_request))
+                connection_time.dialup = Instant::now();
+                Ok((connection_time, send_request))
             } else {
                 let send_request = stream.handshake_http2().await?;
-                let dialup = std::time::Instant::now();
-                Ok((ConnectionTime { dns_lookup, dialup }, send_request))
+                connection_time.dialup = Instant::now();
+                Ok((connection_time, send_request))
             }
         } else {
-            let (dns_lookup, stream) = self.client(url, rng, self.http_version).await?;
+            let (mut connection_time, stream) =
+                self.client(url, rng, self.http_version, connection).await?;
             let send_request = stream.handshake_http2().await?;
-            let dialup = std::time::Instant::now();
-            Ok((ConnectionTime { dns_lookup, dialup }, send_request))
+            connection_time.dialup = Instant::now();
+            Ok((connection_time, send_request))
         }
     }
 
     async fn work_http2(
         &self,
         client_state: &mut ClientStateHttp2,
+    ) -> Result<RequestResult, ClientError> {
+        let first_attempt_start = std::time::Instant::now();
+        let mut retries = 0;
+        let mut reconnect = false;
+        loop {
+            let res = if reconnect {
+                // The connection has failed, so the retry takes a new one
+                match client_state.reconnect(self).await {
+                    Ok(()) => self.work_http2_attempt(client_state).await,
+                    Err(err) => Err(err),
+                }
+            } else {
+                self.work_http2_attempt(client_state).await
+            };
+            if !self.retry.should_retry(&res, retries) {
+                return with_attempts(res, retries, first_attempt_start);
+            }
+            reconnect = is_hyper_error(&res);
+            retries += 1;
+            tokio::time::sleep(self.retry.backoff(retries)).await;
+        }
+    }
+
+    async fn work_http2_attempt(
+        &self,
+        client_state: &mut ClientStateHttp2,
     ) -> Result<RequestResult, ClientError> {
         let do_req = async {
             let (url, rng) = self.generate_url(&mut client_state.rng)?;
@@ -821,32 +1435,40 @@ impl Client {
             let connection_time: Option<ConnectionTime> = None;
 
             let request = self.request(&url)?;
-            match client_state.send_request.send_request(request).await {
+            let request_bytes = request_size(&request);
+            match client_state.http2.send_request.send_request(request).await {
                 Ok(res) => {
                     let (parts, mut stream) = res.into_parts();
-                    let status = parts.status;
 
-                    let mut len_bytes = 0;
-                    while let Some(chunk) = stream.frame().await {
-                        if first_byte.is_none() {
-                            first_byte = Some(std::time::Instant::now())
-                        }
-                        len_bytes += chunk?.data_ref().map(|d| d.len()).unwrap_or_default();
-                    }
+                    let response = self.read_body(parts, &mut stream, &mut first_byte).await?;
 
                     let end = std::time::Instant::now();
-
                     let result = RequestResult {
                         rng,
                         start_latency_correction: None,
                         start,
                         first_byte,
                         end,
-                        status,
-                        len_bytes,
+                        status: response.status,
+                        len_bytes: response.len_bytes,
+                        content_encoding: response.content_encoding,
+                        decoded_len_bytes: response.decoded_len_bytes,
+                        tracked_headers: self.tracked_headers(&response),
+                        url_group: self.url_group(&url),
                         connection_time,
+                        // Redirection isn't supported for HTTP/2
+                        redirects: Vec::new(),
+                        retries: 0,
+                        first_attempt_start: None,
+                        // Set by set_connection_time
+                        remote_ip: None,
+                        request_bytes,
+                        response_header_bytes: response_header_size(&response),
                     };
 
+                    client_state.http2.connection.served(end);
+                    self.check_response(&response, &result)?;
+
                     Ok::<_, ClientError>(result)
                 }
                 Err(e) => Err(e.into()),
@@ -867,18 +1489,35 @@ impl Client {
         }
     }
 
-    #[allow(clippy::type_complexity)]
+    /// Opens a connection to follow a redirect
+    async fn redirect_connect<R: Rng>(
+        &self,
+        url: &Url,
+        rng: &mut R,
+    ) -> Result<SendRequestHttp1, ClientError> {
+        let connection = self.connections.open();
+        let (connection_time, send_request) = self.client_http1(url, rng, &connection).await?;
+        connection.established(&connection_time, true);
+        Ok(send_request)
+    }
+
+    /// Follows `location` with `method` and `body`, recording further redirects into `redirects`
+    #[allow(clippy::too_many_arguments)]
     async fn redirect<R: Rng + Send>(
         &self,
         send_request: SendRequestHttp1,
         base_url: &Url,
         location: &http::header::HeaderValue,
+        method: &http::Method,
+        body: Option<&'static [u8]>,
         limit: usize,
+        redirects: &mut Vec<RedirectHop>,
         rng: &mut R,
-    ) -> Result<(SendRequestHttp1, http::StatusCode, usize), ClientError> {
+    ) -> Result<(SendRequestHttp1, ReadResponse), ClientError> {
         if limit == 0 {
             return Err(ClientError::TooManyRedirect);
         }
+        let start = std::time::Instant::now();
         let url = match Url::parse(location.to_str()?) {
             Ok(url) => url,
             Err(ParseError::RelativeUrlWithoutBase) => Url::options()
@@ -892,16 +1531,14 @@ impl Client {
                 // reuse connection
                 (send_request, None)
             } else {
-                let (_dns_lookup, stream) = self.client_http1(&url, rng).await?;
-                (stream, Some(send_request))
+                (self.redirect_connect(&url, rng).await?, Some(send_request))
             };
 
         while send_request.ready().await.is_err() {
-            let (_dns_lookup, stream) = self.client_http1(&url, rng).await?;
-            send_request = stream;
+            send_request = self.redirect_connect(&url, rng).await?;
         }
 
-        let mut request = self.request(&url)?;
+        let mut request = self.request_with(&url, method, body)?;
         if url.authority() != base_url.authority() {
             request.headers_mut().insert(
                 http::header::HOST,
@@ -910,39 +1547,239 @@ impl Client {
         }
         let res = send_request.send_request(request).await?;
         let (parts, mut stream) = res.into_parts();
-        let mut status = parts.status;
 
-        let mut len_bytes = 0;
-        while let Some(chunk) = stream.frame().await {
-            len_bytes += chunk?.data_ref().map(|d| d.len()).unwrap_or_default();
-        }
+        let mut response = self.read_body(parts, &mut stream, &mut None).await?;
 
-        if let Some(location) = parts.headers.get("Location") {
-            let (send_request_redirect, new_status, len) =
-                Box::pin(self.redirect(send_request, &url, location, limit - 1, rng)).await?;
+        if let Some(location) = response.headers.get("Location").cloned() {
+            redirects.push(RedirectHop {
+                status: response.status,
+                duration: start.elapsed(),
+            });
+            let (method, body) = redirect_method(response.status, method, body);
+            let (send_request_redirect, redirected) = Box::pin(self.redirect(
+                send_request,
+                &url,
+                &location,
+                &method,
+                body,
+                limit - 1,
+                redirects,
+                rng,
+            ))
+            .await?;
             send_request = send_request_redirect;
-            status = new_status;
-            len_bytes = len;
+            response = redirected;
         }
 
         if let Some(send_request_base) = send_request_base {
-            Ok((send_request_base, status, len_bytes))
+            Ok((send_request_base, response))
         } else {
-            Ok((send_request, status, len_bytes))
+            Ok((send_request, response))
+        }
+    }
+
+    /// Reads the response body to the end, decoding it if --decompress is set
+    async fn read_body(
+        &self,
+        parts: http::response::Parts,
+        stream: &mut hyper::body::Incoming,
+        first_byte: &mut Option<std::time::Instant>,
+    ) -> Result<ReadResponse, ClientError> {
+        let keep_body = self.assertions.need_body();
+        let content_encoding = ContentEncoding::from_headers(&parts.headers);
+        let mut decoder = if self.decompress {
+            Decoder::new(content_encoding, keep_body)
+                .map_err(|e| ClientError::DecompressError(content_encoding, e))?
+        } else {
+            None
+        };
+        // The body as received if it isn't decoded
+        let mut body = (keep_body && decoder.is_none()).then(Vec::new);
+
+        let mut len_bytes = 0;
+        while let Some(chunk) = stream.frame().await {
+            if first_byte.is_none() {
+                *first_byte = Some(std::time::Instant::now())
+            }
+            if let Some(data) = chunk?.data_ref() {
+                len_bytes += data.len();
+                if let Some(decoder) = &mut decoder {
+                    decoder
+                        .write(data)
+                        .map_err(|e| ClientError::DecompressError(content_encoding, e))?;
+                }
+                if let Some(body) = &mut body {
+                    body.extend_from_slice(data);
+                }
+            }
+        }
+
+        let decoded = decoder
+            .map(|decoder| decoder.finish())
+            .transpose()
+            .map_err(|e| ClientError::DecompressError(content_encoding, e))?;
+        let decoded_len_bytes = decoded.as_ref().map(|decoded| decoded.len);
+        let body = body.or(decoded.and_then(|decoded| decoded.body));
+
+        Ok(ReadResponse {
+            status: parts.status,
+            headers: parts.headers,
+            len_bytes,
+            content_encoding,
+            decoded_len_bytes,
+            body,
+        })
+    }
+
+    /// Values of the `--track-header` headers of the final response
+    fn tracked_headers(
+        &self,
+        response: &ReadResponse,
+    ) -> Vec<(http::HeaderName, Option<http::HeaderValue>)> {
+        self.track_headers
+            .iter()
+            .map(|name| (name.clone(), response.headers.get(name).cloned()))
+            .collect()
+    }
+
+    /// Key of the per-URL statistics for the requested URL
+    fn url_group(&self, url: &Url) -> Option<String> {
+        let url_groups = self.url_groups.as_ref()?;
+        Some(
+            url_groups
+                .iter()
+                .find(|pattern| pattern.is_match(url.as_str()))
+                .map_or_else(|| url.to_string(), |pattern| pattern.as_str().to_string()),
+        )
+    }
+
+    /// Checks the final response against `--assert-*` options
+    fn check_response(
+        &self,
+        response: &ReadResponse,
+        result: &RequestResult,
+    ) -> Result<(), ClientError> {
+        self.assertions
+            .check(
+                response.status,
+                &response.headers,
+                response.decoded_len_bytes.unwrap_or(response.len_bytes),
+                response.body.as_deref(),
+            )
+            .map_err(|error| ClientError::AssertionFailed {
+                status: result.status,
+                duration: result.duration(),
+                error,
+            })
+    }
+}
+
+/// Connects to `addr` from `bind_address` if specified
+async fn tcp_connect(
+    addr: (std::net::IpAddr, u16),
+    bind_address: Option<std::net::IpAddr>,
+) -> Result<TcpStream, ClientError> {
+    let addr = std::net::SocketAddr::from(addr);
+    let Some(bind_address) = bind_address else {
+        return Ok(TcpStream::connect(addr).await?);
+    };
+    let socket = if addr.is_ipv4() {
+        tokio::net::TcpSocket::new_v4()?
+    } else {
+        tokio::net::TcpSocket::new_v6()?
+    };
+    // Port 0 lets the OS pick an ephemeral port
+    socket.bind(std::net::SocketAddr::new(bind_address, 0))?;
+    Ok(socket.connect(addr).await?)
+}
+
+/// Method and body to follow a redirect response with. See RFC 9110 section 15.4.
+/// Like browsers, POST becomes GET on 301 and 302 too.
+fn redirect_method(
+    status: http::StatusCode,
+    method: &http::Method,
+    body: Option<&'static [u8]>,
+) -> (http::Method, Option<&'static [u8]>) {
+    match status {
+        http::StatusCode::SEE_OTHER if method != http::Method::HEAD => (http::Method::GET, None),
+        http::StatusCode::MOVED_PERMANENTLY | http::StatusCode::FOUND
+            if method == http::Method::POST =>
+        {
+            (http::Method::GET, None)
+        }
+        _ => (method.clone(), body),
+    }
+}
+
+/// Size of the header fields in HTTP/1 form, including the empty line which ends them
+fn header_size(headers: &http::HeaderMap) -> u64 {
+    headers
+        .iter()
+        .map(|(name, value)| (name.as_str().len() + ": ".len() + value.len() + "\r\n".len()) as u64)
+        .sum::<u64>()
+        + "\r\n".len() as u64
+}
+
+/// Size of the request in HTTP/1 form, which is the same whatever the HTTP version to compare requests
+fn request_size(request: &http::Request<Full<Bytes>>) -> u64 {
+    let request_line = request.method().as_str().len()
+        + " ".len()
+        + request.uri().to_string().len()
+        + " HTTP/1.1\r\n".len();
+    let body = hyper::body::Body::size_hint(request.body())
+        .exact()
+        .unwrap_or_default();
+    request_line as u64 + header_size(request.headers()) + body
+}
+
+/// Size of the status line and headers of the response in HTTP/1 form
+fn response_header_size(response: &ReadResponse) -> u64 {
+    let status_line = "HTTP/1.1 200 \r\n".len()
+        + response
+            .status
+            .canonical_reason()
+            .map_or(0, |reason| reason.len());
+    status_line as u64 + header_size(&response.headers)
+}
+
+/// Records the retries into the result of the last attempt, and how long it took to fail into the error
+fn with_attempts(
+    res: Result<RequestResult, ClientError>,
+    retries: u32,
+    first_attempt_start: std::time::Instant,
+) -> Result<RequestResult, ClientError> {
+    match res {
+        Ok(result) if retries == 0 => Ok(result),
+        Ok(mut result) => {
+            result.retries = retries;
+            result.first_attempt_start = Some(first_attempt_start);
+            Ok(result)
+        }
+        Err(err) => {
+            let end = std::time::Instant::now();
+            Err(ClientError::Failed {
+                retries,
+                duration: Some(end - first_attempt_start),
+                end,
+                error: Box::new(err),
+            })
         }
     }
 }
 
 /// Check error and decide whether to cancel the connection
 fn is_cancel_error(res: &Result<RequestResult, ClientError>) -> bool {
-    matches!(res, Err(ClientError::Deadline)) || is_too_many_open_files(res)
+    matches!(
+        res.as_ref().map_err(ClientError::last_attempt),
+        Err(ClientError::Deadline)
+    ) || is_too_many_open_files(res)
 }
 
 /// Check error was "Too many open file"
-fn is_too_many_open_files(res: &Result<RequestResult, ClientError>) -> bool {
+pub(crate) fn is_too_many_open_files(res: &Result<RequestResult, ClientError>) -> bool {
     res.as_ref()
         .err()
-        .map(|err| match err {
+        .map(|err| match err.last_attempt() {
             ClientError::IoError(io_error) => io_error.raw_os_error() == Some(libc::EMFILE),
             _ => false,
         })
@@ -953,7 +1790,7 @@ fn is_too_many_open_files(res: &Result<RequestResult, ClientError>) -> bool {
 fn is_hyper_error(res: &Result<RequestResult, ClientError>) -> bool {
     res.as_ref()
         .err()
-        .map(|err| match err {
+        .map(|err| match err.last_attempt() {
             // REVIEW: IoErrors, if indicating the underlying connection has failed,
             // should also cause a stop of HTTP2 requests
             ClientError::IoError(_) => true,
@@ -963,26 +1800,34 @@ fn is_hyper_error(res: &Result<RequestResult, ClientError>) -> bool {
         .unwrap_or(false)
 }
 
-async fn setup_http2(client: &Client) -> Result<(ConnectionTime, SendRequestHttp2), ClientError> {
+async fn setup_http2(client: &Client, reconnect: bool) -> Result<Http2Connection, ClientError> {
     // Whatever rng state, all urls should have the same authority
     let mut rng: Pcg64Si = SeedableRng::from_seed([0, 0, 0, 0, 0, 0, 0, 0]);
     let url = client.url_generator.generate(&mut rng)?;
-    let (connection_time, send_request) = client.connect_http2(&url, &mut rng).await?;
-
-    Ok((connection_time, send_request))
+    let connection = client.connections.open();
+    let (mut connection_time, send_request) =
+        client.connect_http2(&url, &mut rng, &connection).await?;
+    connection_time.reconnect = reconnect;
+    connection.established(&connection_time, false);
+
+    Ok(Http2Connection {
+        send_request,
+        connection_time,
+        connection,
+        generation: 0,
+    })
 }
 
 async fn work_http2_once(
     client: &Client,
     client_state: &mut ClientStateHttp2,
     report_tx: &kanal::Sender<Result<RequestResult, ClientError>>,
-    connection_time: ConnectionTime,
     start_latency_correction: Option<Instant>,
 ) -> (bool, bool) {
     let mut res = client.work_http2(client_state).await;
     let is_cancel = is_cancel_error(&res);
     let is_reconnect = is_hyper_error(&res);
-    set_connection_time(&mut res, connection_time);
+    set_connection_time(&mut res, client_state.http2.connection_time);
     if let Some(start_latency_correction) = start_latency_correction {
         set_start_latency_correction(&mut res, start_latency_correction);
     }
@@ -993,6 +1838,7 @@ async fn work_http2_once(
 fn set_connection_time<E>(res: &mut Result<RequestResult, E>, connection_time: ConnectionTime) {
     if let Ok(res) = res {
         res.connection_time = Some(connection_time);
+        res.remote_ip = connection_time.remote_ip;
     }
 }
 
@@ -1016,11 +1862,15 @@ pub async fn work_debug<W: Write>(w: &mut W, client: Arc<Client>) -> Result<(),
 
     let response = match client.work_type() {
         HttpWorkType::H2 => {
-            let (_, mut client_state) = client.connect_http2(&url, &mut rng).await?;
+            let (_, mut client_state) = client
+                .connect_http2(&url, &mut rng, &client.connections.open())
+                .await?;
             client_state.send_request(request).await?
         }
         HttpWorkType::H1 => {
-            let (_dns_lookup, mut send_request) = client.client_http1(&url, &mut rng).await?;
+            let (_connection_time, mut send_request) = client
+                .client_http1(&url, &mut rng, &client.connections.open())
+                .await?;
             send_request.send_request(request).await?
         }
     };
@@ -1053,19 +1903,21 @@ pub async fn work(
                     let counter = counter.clone();
                     let client = client.clone();
                     tokio::spawn(async move {
+                        // Connections after the first one replace lost ones
+                        let mut reconnect = false;
                         loop {
-                            match setup_http2(&client).await {
-                                Ok((connection_time, send_request)) => {
+                            match setup_http2(&client, reconnect).await {
+                                Ok(http2) => {
+                                    reconnect = true;
+                                    let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                     let futures = (0..n_http2_parallel)
                                         .map(|_| {
                                             let report_tx = report_tx.clone();
                                             let counter = counter.clone();
                                             let client = client.clone();
 
-                                            let mut client_state = ClientStateHttp2 {
-                                                rng: SeedableRng::from_os_rng(),
-                                                send_request: send_request.clone(),
-                                            };
+                                            let mut client_state =
+                                                ClientStateHttp2::new(&http2, &shared);
                                             tokio::spawn(async move {
                                                 while counter.fetch_add(1, Ordering::Relaxed)
                                                     < n_tasks
@@ -1075,7 +1927,6 @@ pub async fn work(
                                                             &client,
                                                             &mut client_state,
                                                             &report_tx,
-                                                            connection_time,
                                                             None,
                                                         )
                                                         .await;
@@ -1111,7 +1962,7 @@ pub async fn work(
                                 }
                                 Err(err) => {
                                     if counter.fetch_add(1, Ordering::Relaxed) < n_tasks {
-                                        report_tx.send(Err(err)).unwrap();
+                                        report_tx.send(Err(err.unattempted())).unwrap();
                                     } else {
                                         return;
                                     }
@@ -1207,18 +2058,20 @@ pub async fn work_with_qps(
                     let rx = rx.clone();
                     let client = client.clone();
                     tokio::spawn(async move {
+                        // Connections after the first one replace lost ones
+                        let mut reconnect = false;
                         loop {
-                            match setup_http2(&client).await {
-                                Ok((connection_time, send_request)) => {
+                            match setup_http2(&client, reconnect).await {
+                                Ok(http2) => {
+                                    reconnect = true;
+                                    let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                     let futures = (0..n_http2_parallel)
                                         .map(|_| {
                                             let report_tx = report_tx.clone();
                                             let rx = rx.clone();
                                             let client = client.clone();
-                                            let mut client_state = ClientStateHttp2 {
-                                                rng: SeedableRng::from_os_rng(),
-                                                send_request: send_request.clone(),
-                                            };
+                                            let mut client_state =
+                                                ClientStateHttp2::new(&http2, &shared);
                                             tokio::spawn(async move {
                                                 while let Ok(()) = rx.recv().await {
                                                     let (is_cancel, is_reconnect) =
@@ -1226,7 +2079,6 @@ pub async fn work_with_qps(
                                                             &client,
                                                             &mut client_state,
                                                             &report_tx,
-                                                            connection_time,
                                                             None,
                                                         )
                                                         .await;
@@ -1260,7 +2112,7 @@ pub async fn work_with_qps(
                                 Err(err) => {
                                     // Consume a task
                                     if let Ok(()) = rx.recv().await {
-                                        report_tx.send(Err(err)).unwrap();
+                                        report_tx.send(Err(err.unattempted())).unwrap();
                                     } else {
                                         return;
                                     }
@@ -1363,18 +2215,20 @@ pub async fn work_with_qps_latency_correction(
                     let rx = rx.clone();
                     let client = client.clone();
                     tokio::spawn(async move {
+                        // Connections after the first one replace lost ones
+                        let mut reconnect = false;
                         loop {
-                            match setup_http2(&client).await {
-                                Ok((connection_time, send_request)) => {
+                            match setup_http2(&client, reconnect).await {
+                                Ok(http2) => {
+                                    reconnect = true;
+                                    let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                     let futures = (0..n_http2_parallel)
                                         .map(|_| {
                                             let report_tx = report_tx.clone();
                                             let rx = rx.clone();
                                             let client = client.clone();
-                                            let mut client_state = ClientStateHttp2 {
-                                                rng: SeedableRng::from_os_rng(),
-                                                send_request: send_request.clone(),
-                                            };
+                                            let mut client_state =
+                                                ClientStateHttp2::new(&http2, &shared);
                                             tokio::spawn(async move {
                                                 while let Ok(start) = rx.recv().await {
                                                     let (is_cancel, is_reconnect) =
@@ -1382,7 +2236,6 @@ pub async fn work_with_qps_latency_correction(
                                                             &client,
                                                             &mut client_state,
                                                             &report_tx,
-                                                            connection_time,
                                                             Some(start),
                                                         )
                                                         .await;
@@ -1416,7 +2269,7 @@ pub async fn work_with_qps_latency_correction(
                                 Err(err) => {
                                     // Consume a task
                                     if rx.recv().await.is_ok() {
-                                        report_tx.send(Err(err)).unwrap();
+                                        report_tx.send(Err(err.unattempted())).unwrap();
                                     } else {
                                         return;
                                     }
@@ -1484,18 +2337,20 @@ pub async fn work_until(
                     tokio::spawn(async move {
                         let s = s.clone();
                         // Keep trying to establish or re-establish connections up to the deadline
+                        // Connections after the first one replace lost ones
+                        let mut reconnect = false;
                         loop {
-                            match setup_http2(&client).await {
-                                Ok((connection_time, send_request)) => {
+                            match setup_http2(&client, reconnect).await {
+                                Ok(http2) => {
+                                    reconnect = true;
+                                    let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                     // Setup the parallel workers for each HTTP2 connection
                                     let futures = (0..n_http2_parallel)
                                         .map(|_| {
                                             let client = client.clone();
                                             let report_tx = report_tx.clone();
-                                            let mut client_state = ClientStateHttp2 {
-                                                rng: SeedableRng::from_os_rng(),
-                                                send_request: send_request.clone(),
-                                            };
+                                            let mut client_state =
+                                                ClientStateHttp2::new(&http2, &shared);
                                             let s = s.clone();
                                             tokio::spawn(async move {
                                                 // This is where HTTP2 loops to make all the requests for a given client and worker
@@ -1505,7 +2360,6 @@ pub async fn work_until(
                                                             &client,
                                                             &mut client_state,
                                                             &report_tx,
-                                                            connection_time,
                                                             None,
                                                         )
                                                         .await;
@@ -1536,7 +2390,9 @@ pub async fn work_until(
                                                 }
                                             }
                                             _ = s.acquire() => {
-                                                report_tx.send(Err(ClientError::Deadline)).unwrap();
+                                                report_tx
+                                                    .send(Err(ClientError::Deadline.unattempted()))
+                                                    .unwrap();
                                                 connection_gone = true;
                                             }
                                         }
@@ -1547,7 +2403,7 @@ pub async fn work_until(
                                 }
 
                                 Err(err) => {
-                                    report_tx.send(Err(err)).unwrap();
+                                    report_tx.send(Err(err.unattempted())).unwrap();
                                     if s.is_closed() {
                                         break;
                                     }
@@ -1599,7 +2455,9 @@ pub async fn work_until(
                     f.abort();
                     if let Err(e) = f.await {
                         if e.is_cancelled() {
-                            report_tx.send(Err(ClientError::Deadline)).unwrap();
+                            report_tx
+                                .send(Err(ClientError::Deadline.unattempted()))
+                                .unwrap();
                         }
                     }
                 }
@@ -1670,18 +2528,20 @@ pub async fn work_until_with_qps(
                     let rx = rx.clone();
                     let s = s.clone();
                     tokio::spawn(async move {
+                        // Connections after the first one replace lost ones
+                        let mut reconnect = false;
                         loop {
-                            match setup_http2(&client).await {
-                                Ok((connection_time, send_request)) => {
+                            match setup_http2(&client, reconnect).await {
+                                Ok(http2) => {
+                                    reconnect = true;
+                                    let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                     let futures = (0..n_http2_parallel)
                                         .map(|_| {
                                             let client = client.clone();
                                             let report_tx = report_tx.clone();
                                             let rx = rx.clone();
-                                            let mut client_state = ClientStateHttp2 {
-                                                rng: SeedableRng::from_os_rng(),
-                                                send_request: send_request.clone(),
-                                            };
+                                            let mut client_state =
+                                                ClientStateHttp2::new(&http2, &shared);
                                             let s = s.clone();
                                             tokio::spawn(async move {
                                                 while let Ok(()) = rx.recv().await {
@@ -1690,7 +2550,6 @@ pub async fn work_until_with_qps(
                                                             &client,
                                                             &mut client_state,
                                                             &report_tx,
-                                                            connection_time,
                                                             None,
                                                         )
                                                         .await;
@@ -1721,7 +2580,9 @@ pub async fn work_until_with_qps(
                                                 }
                                             }
                                             _ = s.acquire() => {
-                                                report_tx.send(Err(ClientError::Deadline)).unwrap();
+                                                report_tx
+                                                    .send(Err(ClientError::Deadline.unattempted()))
+                                                    .unwrap();
                                                 connection_gone = true;
                                             }
                                         }
@@ -1733,7 +2594,7 @@ pub async fn work_until_with_qps(
                                 Err(err) => {
                                     // Consume a task
                                     if rx.recv().await.is_ok() {
-                                        report_tx.send(Err(err)).unwrap();
+                                        report_tx.send(Err(err.unattempted())).unwrap();
                                     } else {
                                         return;
                                     }
@@ -1790,7 +2651,9 @@ pub async fn work_until_with_qps(
                     f.abort();
                     if let Err(e) = f.await {
                         if e.is_cancelled() {
-                            report_tx.send(Err(ClientError::Deadline)).unwrap();
+                            report_tx
+                                .send(Err(ClientError::Deadline.unattempted()))
+                                .unwrap();
                         }
                     }
                 }
@@ -1860,18 +2723,20 @@ pub async fn work_until_with_qps_latency_correction(
                     let rx = rx.clone();
                     let s = s.clone();
                     tokio::spawn(async move {
+                        // Connections after the first one replace lost ones
+                        let mut reconnect = false;
                         loop {
-                            match setup_http2(&client).await {
-                                Ok((connection_time, send_request)) => {
+                            match setup_http2(&client, reconnect).await {
+                                Ok(http2) => {
+                                    reconnect = true;
+                                    let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                     let futures = (0..n_http2_parallel)
                                         .map(|_| {
                                             let client = client.clone();
                                             let report_tx = report_tx.clone();
                                             let rx = rx.clone();
-                                            let mut client_state = ClientStateHttp2 {
-                                                rng: SeedableRng::from_os_rng(),
-                                                send_request: send_request.clone(),
-                                            };
+                                            let mut client_state =
+                                                ClientStateHttp2::new(&http2, &shared);
                                             let s = s.clone();
                                             tokio::spawn(async move {
                                                 while let Ok(start) = rx.recv().await {
@@ -1880,7 +2745,6 @@ pub async fn work_until_with_qps_latency_correction(
                                                             &client,
                                                             &mut client_state,
                                                             &report_tx,
-                                                            connection_time,
                                                             Some(start),
                                                         )
                                                         .await;
@@ -1910,7 +2774,9 @@ pub async fn work_until_with_qps_latency_correction(
                                                 }
                                             }
                                             _ = s.acquire() => {
-                                                report_tx.send(Err(ClientError::Deadline)).unwrap();
+                                                report_tx
+                                                    .send(Err(ClientError::Deadline.unattempted()))
+                                                    .unwrap();
                                                 connection_gone = true;
                                             }
                                         }
@@ -1922,7 +2788,7 @@ pub async fn work_until_with_qps_latency_correction(
 
                                 Err(err) => {
                                     if rx.recv().await.is_ok() {
-                                        report_tx.send(Err(err)).unwrap();
+                                        report_tx.send(Err(err.unattempted())).unwrap();
                                     } else {
                                         return;
                                     }
@@ -1980,7 +2846,9 @@ pub async fn work_until_with_qps_latency_correction(
                     f.abort();
                     if let Err(e) = f.await {
                         if e.is_cancelled() {
-                            report_tx.send(Err(ClientError::Deadline)).unwrap();
+                            report_tx
+                                .send(Err(ClientError::Deadline.unattempted()))
+                                .unwrap();
                         }
                     }
                 }
@@ -1991,16 +2859,14 @@ pub async fn work_until_with_qps_latency_correction(
 
 /// Optimized workers for `--no-tui` mode
 pub mod fast {
-    use std::sync::Arc;
-
-    use rand::SeedableRng;
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
 
     use crate::{
         client::{
             ClientError, ClientStateHttp1, ClientStateHttp2, HttpWorkType, is_cancel_error,
             is_hyper_error, set_connection_time, setup_http2,
         },
-        result_data::ResultData,
+        result_data::{ResultData, ResultDataOptions},
     };
 
     use super::Client;
@@ -2012,6 +2878,7 @@ pub mod fast {
         n_tasks: usize,
         n_connections: usize,
         n_http2_parallel: usize,
+        result_options: ResultDataOptions,
     ) {
         use std::sync::atomic::{AtomicUsize, Ordering};
         let counter = Arc::new(AtomicUsize::new(0));
@@ -2047,32 +2914,34 @@ pub mod fast {
                         std::thread::spawn(move || {
                             let client = client.clone();
                             let local = tokio::task::LocalSet::new();
+                            // Shared by the tasks of the thread so that memory doesn't grow with -c
+                            let result_data =
+                                Rc::new(RefCell::new(ResultData::new(result_options)));
                             for _ in 0..num_connections {
-                                let report_tx = report_tx.clone();
+                                let result_data = result_data.clone();
                                 let counter = counter.clone();
                                 let client = client.clone();
                                 let token = token.clone();
                                 local.spawn_local(Box::pin(async move {
-                                    let mut has_err = false;
-                                    let mut result_data_err = ResultData::default();
+                                    // Connections after the first one replace lost ones
+                                    let mut reconnect = false;
                                     loop {
                                         let client = client.clone();
-                                        match setup_http2(&client).await {
-                                            Ok((connection_time, send_request)) => {
+                                        match setup_http2(&client, reconnect).await {
+                                            Ok(http2) => {
+                                                reconnect = true;
+                                                let shared = Arc::new(tokio::sync::Mutex::new(
+                                                    http2.clone(),
+                                                ));
                                                 let futures = (0..n_http2_parallel)
                                                     .map(|_| {
-                                                        let mut client_state = ClientStateHttp2 {
-                                                            rng: SeedableRng::from_os_rng(),
-                                                            send_request: send_request.clone(),
-                                                        };
+                                                        let mut client_state =
+                                                            ClientStateHttp2::new(&http2, &shared);
                                                         let counter = counter.clone();
                                                         let client = client.clone();
-                                                        let report_tx = report_tx.clone();
+                                                        let result_data = result_data.clone();
                                                         let token = token.clone();
                                                         tokio::task::spawn_local(async move {
-                                                            let mut result_data =
-                                                                ResultData::default();
-
                                                             let work = async {
                                                                 while counter
                                                                     .fetch_add(1, Ordering::Relaxed)
@@ -2089,10 +2958,14 @@ pub mod fast {
                                                                         is_hyper_error(&res);
                                                                     set_connection_time(
                                                                         &mut res,
-                                                                        connection_time,
+                                                                        client_state
+                                                                            .http2
+                                                                            .connection_time,
                                                                     );
 
-                                                                    result_data.push(res);
+                                                                    result_data
+                                                                        .borrow_mut()
+                                                                        .push(res);
 
                                                                     if is_cancel || is_reconnect {
                                                                         return is_cancel;
@@ -2109,8 +2982,6 @@ pub mod fast {
                                                                     true
                                                                 }
                                                             };
-
-                                                            report_tx.send(result_data).unwrap();
                                                             is_cancel
                                                         })
                                                     })
@@ -2138,21 +3009,20 @@ pub mod fast {
                                             Err(err) => {
                                                 if counter.fetch_add(1, Ordering::Relaxed) < n_tasks
                                                 {
-                                                    has_err = true;
-                                                    result_data_err.push(Err(err));
+                                                    result_data
+                                                        .borrow_mut()
+                                                        .push(Err(err.unattempted()));
                                                 } else {
                                                     break;
                                                 }
                                             }
                                         }
                                     }
-                                    if has_err {
-                                        report_tx.send(result_data_err).unwrap();
-                                    }
                                 }));
                             }
 
                             rt.block_on(local);
+                            report_tx.send(result_data.take()).unwrap();
                         })
                     })
                     .collect::<Vec<_>>()
@@ -2170,15 +3040,15 @@ pub mod fast {
                     let token = token.clone();
                     std::thread::spawn(move || {
                         let local = tokio::task::LocalSet::new();
+                        // Shared by the tasks of the thread so that memory doesn't grow with -c
+                        let result_data = Rc::new(RefCell::new(ResultData::new(result_options)));
 
                         for _ in 0..num_connection {
-                            let report_tx = report_tx.clone();
+                            let result_data = result_data.clone();
                             let counter = counter.clone();
                             let client = client.clone();
                             let token = token.clone();
                             local.spawn_local(Box::pin(async move {
-                                let mut result_data = ResultData::default();
-
                                 tokio::select! {
                                     _ = token.cancelled() => {}
                                     _ = async {
@@ -2186,17 +3056,17 @@ pub mod fast {
                                         while counter.fetch_add(1, Ordering::Relaxed) < n_tasks {
                                             let res = client.work_http1(&mut client_state).await;
                                             let is_cancel = is_cancel_error(&res);
-                                            result_data.push(res);
+                                            result_data.borrow_mut().push(res);
                                             if is_cancel {
                                                 break;
                                             }
                                         }
                                     } => {}
                                 }
-                                report_tx.send(result_data).unwrap();
                             }));
                         }
                         rt.block_on(local);
+                        report_tx.send(result_data.take()).unwrap();
                     })
                 })
                 .collect::<Vec<_>>(),
@@ -2222,6 +3092,7 @@ pub mod fast {
         n_connections: usize,
         n_http2_parallel: usize,
         wait_ongoing_requests_after_deadline: bool,
+        result_options: ResultDataOptions,
     ) {
         use std::sync::atomic::{AtomicBool, Ordering};
         let num_threads = num_cpus::get_physical();
@@ -2257,31 +3128,30 @@ pub mod fast {
                     std::thread::spawn(move || {
                         let client = client.clone();
                         let local = tokio::task::LocalSet::new();
+                        // Shared by the tasks of the thread so that memory doesn't grow with -c
+                        let result_data = Rc::new(RefCell::new(ResultData::new(result_options)));
                         for _ in 0..num_connections {
-                            let report_tx = report_tx.clone();
+                            let result_data = result_data.clone();
                             let client = client.clone();
                             let token = token.clone();
                             let is_end = is_end.clone();
                             local.spawn_local(Box::pin(async move {
-                                let mut has_err = false;
-                                let mut result_data_err = ResultData::default();
+                                // Connections after the first one replace lost ones
+                                let mut reconnect = false;
                                 loop {
                                     let client = client.clone();
-                                    match setup_http2(&client).await {
-                                        Ok((connection_time, send_request)) => {
+                                    match setup_http2(&client, reconnect).await {
+                                        Ok(http2) => {
+                                            reconnect = true;
+                                            let shared = Arc::new(tokio::sync::Mutex::new(http2.clone()));
                                             let futures = (0..n_http2_parallel)
                                                 .map(|_| {
-                                                    let mut client_state = ClientStateHttp2 {
-                                                        rng: SeedableRng::from_os_rng(),
-                                                        send_request: send_request.clone(),
-                                                    };
+                                                    let mut client_state = ClientStateHttp2::new(&http2, &shared);
                                                     let client = client.clone();
-                                                    let report_tx = report_tx.clone();
+                                                    let result_data = result_data.clone();
                                                     let token = token.clone();
                                                     let is_end = is_end.clone();
                                                     tokio::task::spawn_local(async move {
-                                                        let mut result_data = ResultData::default();
-
                                                         let work = async {
                                                             loop {
                                                                 let mut res = client
@@ -2291,10 +3161,10 @@ pub mod fast {
                                                                 let is_reconnect = is_hyper_error(&res);
                                                                 set_connection_time(
                                                                     &mut res,
-                                                                    connection_time,
+                                                                    client_state.http2.connection_time,
                                                                 );
 
-                                                                result_data.push(res);
+                                                                result_data.borrow_mut().push(res);
 
                                                                 if is_cancel || is_reconnect {
                                                                     return is_cancel;
@@ -2307,12 +3177,10 @@ pub mod fast {
                                                                 is_cancel
                                                             }
                                                             _ = token.cancelled() => {
-                                                                result_data.push(Err(ClientError::Deadline));
+                                                                result_data.borrow_mut().push(Err(ClientError::Deadline.unattempted()));
                                                                 true
                                                             }
                                                         };
-
-                                                        report_tx.send(result_data).unwrap();
                                                         is_cancel
                                                     })
                                                 })
@@ -2338,21 +3206,18 @@ pub mod fast {
                                             }
                                         }
                                         Err(err) => {
-                                            has_err = true;
-                                            result_data_err.push(Err(err));
+                                            result_data.borrow_mut().push(Err(err.unattempted()));
                                             if is_end.load(Ordering::Relaxed) {
                                                 break;
                                             }
                                         }
                                     }
                                 }
-                                if has_err {
-                                    report_tx.send(result_data_err).unwrap();
-                                }
                             }));
                         }
 
                         rt.block_on(local);
+                        report_tx.send(result_data.take()).unwrap();
                     })
                 })
                 .collect::<Vec<_>>()
@@ -2370,21 +3235,21 @@ pub mod fast {
                     let token = token.clone();
                     std::thread::spawn(move || {
                         let local = tokio::task::LocalSet::new();
+                        // Shared by the tasks of the thread so that memory doesn't grow with -c
+                        let result_data = Rc::new(RefCell::new(ResultData::new(result_options)));
 
                         for _ in 0..num_connection {
-                            let report_tx = report_tx.clone();
+                            let result_data = result_data.clone();
                             let is_end = is_end.clone();
                             let client = client.clone();
                             let token = token.clone();
                             local.spawn_local(Box::pin(async move {
-                                let mut result_data = ResultData::default();
-
                                 let work = async {
                                     let mut client_state = ClientStateHttp1::default();
                                     loop {
                                         let res = client.work_http1(&mut client_state).await;
                                         let is_cancel = is_cancel_error(&res);
-                                        result_data.push(res);
+                                        result_data.borrow_mut().push(res);
                                         if is_cancel || is_end.load(Ordering::Relaxed) {
                                             break;
                                         }
@@ -2395,13 +3260,13 @@ pub mod fast {
                                     _ = work => {
                                     }
                                     _ = token.cancelled() => {
-                                        result_data.push(Err(ClientError::Deadline));
+                                        result_data.borrow_mut().push(Err(ClientError::Deadline.unattempted()));
                                     }
                                 }
-                                report_tx.send(result_data).unwrap();
                             }));
                         }
                         rt.block_on(local);
+                        report_tx.send(result_data.take()).unwrap();
                     })
                 })
                 .collect::<Vec<_>>(),
@@ -2425,3 +3290,25 @@ pub mod fast {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hosts() {
+        let hosts = parse_hosts(
+            "# comment
+127.0.0.1 localhost Blue.example
+::1       localhost # trailing comment
+10.0.0.2\tgreen.example blue.example
+not-an-ip ignored.example
+",
+        );
+        let ip = |s: &str| s.parse::<std::net::IpAddr>().unwrap();
+        assert_eq!(hosts["localhost"], vec![ip("127.0.0.1"), ip("::1")]);
+        assert_eq!(hosts["blue.example"], vec![ip("127.0.0.1"), ip("10.0.0.2")]);
+        assert_eq!(hosts["green.example"], vec![ip("10.0.0.2")]);
+        assert!(!hosts.contains_key("ignored.example"));
+    }
+}
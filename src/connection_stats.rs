@@ -0,0 +1,396 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, OnceLock, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::{client::ConnectionTime, counting_stream::SocketBytes, result_data::MinMaxMean};
+
+/// Connections opened during a run. Each of them is tracked only while it's open and folded into the totals when it's closed,
+/// so the memory is bounded by the number of concurrent connections rather than the number of requests.
+#[derive(Debug, Default)]
+pub struct ConnectionRegistry {
+    /// Start of the run and the width of the buckets of `ConnectionStat::bytes_series`
+    time_series: OnceLock<(Instant, Duration)>,
+    state: Mutex<RegistryState>,
+}
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    next_id: u64,
+    open: HashMap<u64, Weak<Connection>>,
+    closed: ConnectionStat,
+}
+
+impl ConnectionRegistry {
+    /// Starts tracking a new connection, which is folded into the totals when the returned `Connection` is dropped
+    pub fn open(self: &Arc<Self>) -> Arc<Connection> {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        let connection = Arc::new(Connection {
+            id,
+            registry: self.clone(),
+            bytes: SocketBytes::default(),
+            usage: Mutex::new(Usage::default()),
+        });
+        state.open.insert(id, Arc::downgrade(&connection));
+        connection
+    }
+
+    /// Buckets the bytes of the connections in `ConnectionStat::bytes_series` like `ResultDataOptions::time_series`.
+    /// Only the first call has effect.
+    pub fn set_time_series(&self, start: Instant, interval: Duration) {
+        let _ = self.time_series.set((start, interval));
+    }
+
+    /// Returns the stats of the closed connections and the open ones so far
+    pub fn stat(&self) -> ConnectionStat {
+        let (mut stat, open) = {
+            let state = self.state.lock().unwrap();
+            (
+                state.closed.clone(),
+                state
+                    .open
+                    .values()
+                    .filter_map(Weak::upgrade)
+                    .collect::<Vec<_>>(),
+            )
+        };
+        for connection in &open {
+            stat.add(connection);
+        }
+        // Dropping the last reference locks the registry, so this has to be out of the lock above
+        drop(open);
+        stat
+    }
+}
+
+/// A connection being tracked. Shared by its stream and the requests sent on it.
+#[derive(Debug)]
+pub struct Connection {
+    id: u64,
+    registry: Arc<ConnectionRegistry>,
+    /// Counted by `CountingStream`
+    pub bytes: SocketBytes,
+    usage: Mutex<Usage>,
+}
+
+/// How many `ConnectionStat::dns_change_times` are kept
+pub const MAX_DNS_CHANGE_TIMES: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Usage {
+    /// None until the connection is established
+    dialup: Option<Instant>,
+    /// `dns_lookup` if the lookup found changed addresses
+    dns_change: Option<Instant>,
+    tls: bool,
+    /// Whether the TLS handshake resumed a previous session, once it completed
+    tls_resumed: Option<bool>,
+    reconnect: bool,
+    redirect: bool,
+    /// Requests which got a response on the connection, whether or not it passed the assertions
+    requests: usize,
+    /// End of the last of `requests`
+    last_end: Option<Instant>,
+    /// (sent, received) bytes already added to `ConnectionStat::bytes_series`
+    reported_bytes: (u64, u64),
+}
+
+impl Usage {
+    /// Returns the (sent, received) bytes of the totals not reported yet
+    fn unreported_bytes(&self, (sent, received): (u64, u64)) -> (u64, u64) {
+        (
+            sent - self.reported_bytes.0,
+            received - self.reported_bytes.1,
+        )
+    }
+}
+
+impl Connection {
+    /// Records that the connection became ready to send requests. `redirect` is for connections opened to follow redirects.
+    pub fn established(&self, connection_time: &ConnectionTime, redirect: bool) {
+        let mut usage = self.usage.lock().unwrap();
+        usage.dialup = Some(connection_time.dialup);
+        usage.dns_change = connection_time
+            .dns_changed
+            .then_some(connection_time.dns_lookup);
+        usage.tls = connection_time.tls_handshake.is_some();
+        usage.reconnect = connection_time.reconnect;
+        usage.redirect = redirect;
+    }
+
+    /// Records that the TLS handshake completed
+    #[cfg(feature = "rustls")]
+    pub fn tls_handshaken(&self, resumed: bool) {
+        self.usage.lock().unwrap().tls_resumed = Some(resumed);
+    }
+
+    /// Records a request whose response ended at `end`, whether or not it passed the assertions
+    pub fn served(&self, end: Instant) {
+        let mut usage = self.usage.lock().unwrap();
+        usage.requests += 1;
+        usage.last_end = Some(usage.last_end.map_or(end, |last_end| last_end.max(end)));
+        if let Some(&time_series) = self.registry.time_series.get() {
+            let total = self.bytes.get();
+            let bytes = usage.unreported_bytes(total);
+            usage.reported_bytes = total;
+            drop(usage);
+            self.registry
+                .state
+                .lock()
+                .unwrap()
+                .closed
+                .add_series_bytes(time_series, end, bytes);
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let mut state = self.registry.state.lock().unwrap();
+        state.open.remove(&self.id);
+        state.closed.add(self);
+    }
+}
+
+/// Connections established, whether or not any request succeeded on them
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStat {
+    pub connections: usize,
+    pub tls_connections: usize,
+    /// TLS connections which completed the handshake. Not counted with native-tls, which can't tell resumption.
+    pub tls_handshakes: usize,
+    /// Of `tls_handshakes`, which resumed a previous session
+    pub tls_resumed: usize,
+    /// Connections which replaced lost kept-alive ones
+    pub reconnects: usize,
+    /// Connections opened to follow redirects. They aren't in `connections`.
+    pub redirect_connections: usize,
+    /// Number of connections by how many requests they served
+    pub requests_per_connection: BTreeMap<usize, usize>,
+    /// From when each connection was established to the end of the last response on it.
+    /// Connections which served no request aren't in it.
+    pub lifetime: MinMaxMean,
+    /// Lookups which found changed addresses, of every established connection
+    pub dns_changes: usize,
+    /// When up to `MAX_DNS_CHANGE_TIMES` of `dns_changes` happened in ascending order
+    pub dns_change_times: Vec<Instant>,
+    /// Written to the sockets of every connection, including connecting, TLS and HTTP framing
+    pub bytes_sent: u64,
+    /// Read from the sockets of every connection, including connecting, TLS and HTTP framing
+    pub bytes_received: u64,
+    /// (sent, received) bytes in each bucket of the time series, by when a request on the connection ended or it was closed.
+    /// Empty unless `ConnectionRegistry::set_time_series` is called.
+    pub bytes_series: Vec<(u64, u64)>,
+}
+
+impl ConnectionStat {
+    fn add(&mut self, connection: &Connection) {
+        let usage = connection.usage.lock().unwrap();
+        // Read in the lock so that it's at least `usage.reported_bytes`.
+        // Also of connections which failed to be established.
+        let (sent, received) = connection.bytes.get();
+        let usage = *usage;
+        self.bytes_sent += sent;
+        self.bytes_received += received;
+        if let Some(&time_series) = connection.registry.time_series.get() {
+            self.add_series_bytes(
+                time_series,
+                Instant::now(),
+                usage.unreported_bytes((sent, received)),
+            );
+        }
+        let Some(dialup) = usage.dialup else {
+            return;
+        };
+        if let Some(dns_change) = usage.dns_change {
+            self.dns_changes += 1;
+            if self.dns_change_times.len() < MAX_DNS_CHANGE_TIMES {
+                let i = self.dns_change_times.partition_point(|&t| t <= dns_change);
+                self.dns_change_times.insert(i, dns_change);
+            }
+        }
+        if usage.redirect {
+            self.redirect_connections += 1;
+            return;
+        }
+        self.connections += 1;
+        self.tls_connections += usage.tls as usize;
+        if let Some(resumed) = usage.tls_resumed {
+            self.tls_handshakes += 1;
+            self.tls_resumed += resumed as usize;
+        }
+        self.reconnects += usage.reconnect as usize;
+        *self
+            .requests_per_connection
+            .entry(usage.requests)
+            .or_insert(0) += 1;
+        if let Some(last_end) = usage.last_end {
+            self.lifetime
+                .add(last_end.saturating_duration_since(dialup).as_secs_f64());
+        }
+    }
+
+    fn add_series_bytes(
+        &mut self,
+        (start, interval): (Instant, Duration),
+        time: Instant,
+        (sent, received): (u64, u64),
+    ) {
+        let i = (time.saturating_duration_since(start).as_nanos() / interval.as_nanos()) as usize;
+        if self.bytes_series.len() <= i {
+            self.bytes_series.resize(i + 1, (0, 0));
+        }
+        self.bytes_series[i].0 += sent;
+        self.bytes_series[i].1 += received;
+    }
+
+    /// Returns (percentile, requests served by a connection)
+    pub fn requests_per_connection_percentiles(&self, percentiles: &[f64]) -> Vec<(f64, usize)> {
+        percentiles
+            .iter()
+            .filter_map(|&p| {
+                let rank = ((p / 100.0 * self.connections as f64).ceil() as usize).max(1);
+                let mut seen = 0;
+                self.requests_per_connection
+                    .iter()
+                    .find(|&(_, &n)| {
+                        seen += n;
+                        seen >= rank
+                    })
+                    .map(|(&requests, _)| (p, requests))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_connection_stat() {
+        let registry = Arc::new(ConnectionRegistry::default());
+        let stat = registry.stat();
+        assert_eq!(stat.connections, 0);
+        assert!(stat.requests_per_connection_percentiles(&[50.0]).is_empty());
+
+        let now = Instant::now();
+        let connection_time = |dialup: u64, tls: bool, reconnect: bool| ConnectionTime {
+            tls_handshake: tls.then_some(now),
+            reconnect,
+            ..ConnectionTime::new(now, now + Duration::from_millis(dialup))
+        };
+
+        let first = registry.open();
+        first.established(&connection_time(10, true, false), false);
+        for end in [30, 60, 40] {
+            first.served(now + Duration::from_millis(end));
+        }
+        let second = registry.open();
+        second.established(&connection_time(100, true, true), false);
+        second.served(now + Duration::from_millis(120));
+        // Not established
+        let failed = registry.open();
+        // Established but served no request
+        let unused = registry.open();
+        unused.established(
+            &ConnectionTime {
+                dns_changed: true,
+                ..connection_time(10, false, false)
+            },
+            false,
+        );
+        let redirect = registry.open();
+        redirect.established(&connection_time(10, false, false), true);
+        redirect.served(now + Duration::from_millis(20));
+
+        let stat = registry.stat();
+        assert_eq!(stat.connections, 3);
+        assert_eq!(stat.tls_connections, 2);
+        assert_eq!(stat.reconnects, 1);
+        assert_eq!(stat.redirect_connections, 1);
+        assert_eq!(
+            stat.requests_per_connection,
+            [(0, 1), (1, 1), (3, 1)].into()
+        );
+        assert_eq!(
+            stat.requests_per_connection_percentiles(&[50.0, 100.0]),
+            vec![(50.0, 1), (100.0, 3)]
+        );
+        assert!((stat.lifetime.min() - 0.02).abs() < 1e-9);
+        assert!((stat.lifetime.max() - 0.05).abs() < 1e-9);
+        assert_eq!(stat.dns_changes, 1);
+        assert_eq!(stat.dns_change_times, vec![now]);
+
+        // Closed connections are folded in and forgotten
+        drop((first, failed, unused, redirect));
+        assert_eq!(registry.state.lock().unwrap().open.len(), 1);
+        let stat = registry.stat();
+        assert_eq!(stat.connections, 3);
+        assert_eq!(stat.redirect_connections, 1);
+        assert_eq!(stat.dns_changes, 1);
+        drop(second);
+        assert!(registry.state.lock().unwrap().open.is_empty());
+        assert_eq!(
+            registry.stat().requests_per_connection,
+            [(0, 1), (1, 1), (3, 1)].into()
+        );
+    }
+
+    #[test]
+    fn test_bytes_series() {
+        use std::sync::atomic::Ordering::Relaxed;
+
+        let registry = Arc::new(ConnectionRegistry::default());
+        let start = Instant::now();
+        registry.set_time_series(start, Duration::from_secs(1));
+
+        let connection = registry.open();
+        connection.bytes.sent.fetch_add(100, Relaxed);
+        connection.bytes.received.fetch_add(1000, Relaxed);
+        connection.served(start + Duration::from_millis(1500));
+        connection.bytes.sent.fetch_add(10, Relaxed);
+        connection.served(start + Duration::from_millis(2500));
+        assert_eq!(
+            registry.stat().bytes_series,
+            vec![(0, 0), (100, 1000), (10, 0)]
+        );
+
+        // The rest is counted when it's closed
+        connection.bytes.received.fetch_add(1, Relaxed);
+        drop(connection);
+        let stat = registry.stat();
+        assert_eq!(stat.bytes_series.iter().map(|b| b.0).sum::<u64>(), 110);
+        assert_eq!(stat.bytes_series.iter().map(|b| b.1).sum::<u64>(), 1001);
+    }
+
+    #[cfg(feature = "rustls")]
+    #[test]
+    fn test_tls_resumption() {
+        let registry = Arc::new(ConnectionRegistry::default());
+        let now = Instant::now();
+        let connection_time = ConnectionTime {
+            tls_handshake: Some(now),
+            ..ConnectionTime::new(now, now)
+        };
+        // The last one is still handshaking like with 0-RTT early data
+        let _connections = [Some(false), Some(true), Some(true), None].map(|resumed| {
+            let connection = registry.open();
+            connection.established(&connection_time, false);
+            if let Some(resumed) = resumed {
+                connection.tls_handshaken(resumed);
+            }
+            connection
+        });
+
+        let stat = registry.stat();
+        assert_eq!(stat.tls_connections, 4);
+        assert_eq!(stat.tls_handshakes, 3);
+        assert_eq!(stat.tls_resumed, 2);
+    }
+}
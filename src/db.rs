@@ -1,6 +1,11 @@
+use std::collections::BTreeMap;
+
 use rusqlite::Connection;
 
-use crate::client::{Client, RequestResult};
+use crate::{
+    client::{Client, ErrorKind, RequestResult},
+    result_data::ErrorKindStat,
+};
 
 fn create_db(conn: &Connection) -> Result<usize, rusqlite::Error> {
     conn.execute(
@@ -12,6 +17,25 @@ fn create_db(conn: &Connection) -> Result<usize, rusqlite::Error> {
             duration REAL NOT NULL,
             status INTEGER NOT NULL,
             len_bytes INTEGER NOT NULL,
+            run INTEGER NOT NULL,
+            dns_lookup REAL,
+            tcp_connect REAL,
+            tls_handshake REAL,
+            proxy_connect REAL,
+            content_encoding TEXT NOT NULL,
+            decoded_len_bytes INTEGER,
+            ttfb REAL,
+            download_time REAL
+        )",
+        (),
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS oha_errors (
+            kind TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            sample TEXT NOT NULL,
+            first_seen REAL NOT NULL,
+            last_seen REAL NOT NULL,
             run INTEGER NOT NULL
         )",
         (),
@@ -23,6 +47,7 @@ pub fn store(
     db_url: &str,
     start: std::time::Instant,
     request_records: &[RequestResult],
+    error_kinds: &BTreeMap<ErrorKind, ErrorKindStat>,
     run: u64,
 ) -> Result<usize, rusqlite::Error> {
     let mut conn = Connection::open(db_url)?;
@@ -33,8 +58,9 @@ pub fn store(
 
     for request in request_records {
         let url = client.generate_url(&mut request.rng.clone()).unwrap().0;
+        let connection_time = request.connection_time.as_ref();
         affected_rows += t.execute(
-            "INSERT INTO oha (url, start, start_latency_correction, end, duration, status, len_bytes, run) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO oha (url, start, start_latency_correction, end, duration, status, len_bytes, run, dns_lookup, tcp_connect, tls_handshake, proxy_connect, content_encoding, decoded_len_bytes, ttfb, download_time) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             (
                 url.to_string(),
                 (request.start - start).as_secs_f64(),
@@ -43,7 +69,29 @@ pub fn store(
                 request.duration().as_secs_f64(),
                 request.status.as_u16() as i64,
                 request.len_bytes,
-                run
+                run,
+                connection_time.map(|ct| (ct.dns_lookup - request.start).as_secs_f64()),
+                connection_time.map(|ct| ct.tcp_connect_duration().as_secs_f64()),
+                connection_time.and_then(|ct| ct.tls_handshake_duration()).map(|d| d.as_secs_f64()),
+                connection_time.and_then(|ct| ct.proxy_connect_duration()).map(|d| d.as_secs_f64()),
+                request.content_encoding.as_str(),
+                request.decoded_len_bytes,
+                request.ttfb().map(|d| d.as_secs_f64()),
+                request.download_time().map(|d| d.as_secs_f64()),
+            ),
+        )?;
+    }
+
+    for (kind, stat) in error_kinds {
+        affected_rows += t.execute(
+            "INSERT INTO oha_errors (kind, count, sample, first_seen, last_seen, run) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                kind.as_str(),
+                stat.count,
+                &stat.sample,
+                (stat.first_seen - start).as_secs_f64(),
+                (stat.last_seen - start).as_secs_f64(),
+                run,
             ),
         )?;
     }
@@ -58,6 +106,7 @@ mod test_db {
     use rand::SeedableRng;
 
     use super::*;
+    use crate::decompress::ContentEncoding;
 
     #[test]
     fn test_store() {
@@ -70,15 +119,34 @@ mod test_db {
             rng: SeedableRng::seed_from_u64(0),
             status: hyper::StatusCode::OK,
             len_bytes: 100,
+            content_encoding: ContentEncoding::Identity,
+            decoded_len_bytes: None,
+            tracked_headers: Vec::new(),
+            redirects: Vec::new(),
+            retries: 0,
+            first_attempt_start: None,
+            url_group: None,
+            request_bytes: 0,
+            response_header_bytes: 0,
             start_latency_correction: None,
             start: std::time::Instant::now(),
             connection_time: None,
             first_byte: None,
             end: std::time::Instant::now(),
+            remote_ip: None,
         };
         let test_vec = vec![test_val.clone(), test_val.clone()];
         let client = Client::default();
-        let result = store(&client, ":memory:", start, &test_vec, run);
-        assert_eq!(result.unwrap(), 2);
+        let error_kinds = BTreeMap::from([(
+            ErrorKind::Timeout,
+            ErrorKindStat {
+                count: 3,
+                sample: "timeout".to_string(),
+                first_seen: start,
+                last_seen: std::time::Instant::now(),
+            },
+        )]);
+        let result = store(&client, ":memory:", start, &test_vec, &error_kinds, run);
+        assert_eq!(result.unwrap(), 3);
     }
 }
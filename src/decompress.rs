@@ -0,0 +1,279 @@
+use hyper::http;
+use std::io::Write;
+
+/// Content-Encoding of a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContentEncoding {
+    Identity,
+    Gzip,
+    Deflate,
+    Br,
+    Zstd,
+    /// Encodings we can't decode, including stacked ones like `gzip, br`
+    Other,
+}
+
+impl ContentEncoding {
+    pub fn from_headers(headers: &http::HeaderMap) -> Self {
+        let mut values = headers.get_all(http::header::CONTENT_ENCODING).iter();
+        let Some(value) = values.next() else {
+            return Self::Identity;
+        };
+        if values.next().is_some() {
+            return Self::Other;
+        }
+        let Ok(value) = value.to_str() else {
+            return Self::Other;
+        };
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => Self::Identity,
+            "gzip" | "x-gzip" => Self::Gzip,
+            "deflate" => Self::Deflate,
+            "br" => Self::Br,
+            "zstd" => Self::Zstd,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Identity => "identity",
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+            Self::Br => "br",
+            Self::Zstd => "zstd",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Counts decoded bytes, keeping them only if needed
+#[derive(Default)]
+pub struct Decoded {
+    pub len: usize,
+    /// None unless the body is kept
+    pub body: Option<Vec<u8>>,
+}
+
+impl Decoded {
+    fn new(keep_body: bool) -> Self {
+        Self {
+            len: 0,
+            body: keep_body.then(Vec::new),
+        }
+    }
+}
+
+impl Write for Decoded {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.len += buf.len();
+        if let Some(body) = &mut self.body {
+            body.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Streaming decoder of a response body which counts the decoded size
+pub struct Decoder(Inner);
+
+enum Inner {
+    Identity(Decoded),
+    Gzip(flate2::write::GzDecoder<Decoded>),
+    // "deflate" in HTTP is the zlib format
+    Deflate(flate2::write::ZlibDecoder<Decoded>),
+    Br(Box<brotli::DecompressorWriter<Decoded>>),
+    Zstd(Box<ZstdDecoder>),
+}
+
+/// `zstd::stream::write::Decoder` can't tell whether the last frame is complete
+struct ZstdDecoder {
+    decoder: zstd::stream::raw::Decoder<'static>,
+    out: Decoded,
+    buf: Vec<u8>,
+    /// The hint of the last step, 0 when a frame has just been finished
+    remaining: usize,
+}
+
+impl ZstdDecoder {
+    fn new(out: Decoded) -> std::io::Result<Self> {
+        Ok(Self {
+            decoder: zstd::stream::raw::Decoder::new()?,
+            out,
+            buf: vec![0; zstd::zstd_safe::DCtx::out_size()],
+            remaining: 0,
+        })
+    }
+
+    fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        use zstd::stream::raw::{InBuffer, Operation, OutBuffer};
+
+        let mut input = InBuffer::around(data);
+        loop {
+            let mut output = OutBuffer::around(self.buf.as_mut_slice());
+            self.remaining = self.decoder.run(&mut input, &mut output)?;
+            let written = output.pos();
+            let full = written == self.buf.len();
+            self.out.write_all(&self.buf[..written])?;
+            // A full output buffer may leave decoded data in the context
+            if input.pos() == data.len() && !full {
+                return Ok(());
+            }
+        }
+    }
+
+    fn finish(self) -> std::io::Result<Decoded> {
+        // `write` has already drained the context
+        if self.remaining != 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Truncated zstd frame",
+            ));
+        }
+        Ok(self.out)
+    }
+}
+
+impl Decoder {
+    /// None if the encoding isn't supported
+    pub fn new(encoding: ContentEncoding, keep_body: bool) -> std::io::Result<Option<Self>> {
+        let decoded = Decoded::new(keep_body);
+        Ok(Some(Self(match encoding {
+            ContentEncoding::Identity => Inner::Identity(decoded),
+            ContentEncoding::Gzip => Inner::Gzip(flate2::write::GzDecoder::new(decoded)),
+            ContentEncoding::Deflate => Inner::Deflate(flate2::write::ZlibDecoder::new(decoded)),
+            ContentEncoding::Br => {
+                Inner::Br(Box::new(brotli::DecompressorWriter::new(decoded, 4096)))
+            }
+            ContentEncoding::Zstd => Inner::Zstd(Box::new(ZstdDecoder::new(decoded)?)),
+            ContentEncoding::Other => return Ok(None),
+        })))
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match &mut self.0 {
+            Inner::Identity(w) => w.write_all(data),
+            Inner::Gzip(w) => w.write_all(data),
+            Inner::Deflate(w) => w.write_all(data),
+            Inner::Br(w) => w.write_all(data),
+            Inner::Zstd(w) => w.write(data),
+        }
+    }
+
+    /// Finishes decoding
+    pub fn finish(self) -> std::io::Result<Decoded> {
+        Ok(match self.0 {
+            Inner::Identity(w) => w,
+            Inner::Gzip(w) => w.finish()?,
+            Inner::Deflate(w) => w.finish()?,
+            Inner::Br(w) => w.into_inner().map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid brotli stream")
+            })?,
+            Inner::Zstd(w) => w.finish()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoded_len(encoding: ContentEncoding, data: &[u8]) -> std::io::Result<usize> {
+        let mut decoder = Decoder::new(encoding, false)?.unwrap();
+        // Feed in small chunks like a streamed body
+        for chunk in data.chunks(7) {
+            decoder.write(chunk)?;
+        }
+        Ok(decoder.finish()?.len)
+    }
+
+    #[test]
+    fn test_decoder() {
+        let body = "Hello World! ".repeat(100);
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(body.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+        assert!(gzip.len() < body.len());
+        assert_eq!(
+            decoded_len(ContentEncoding::Gzip, &gzip).unwrap(),
+            body.len()
+        );
+        assert!(decoded_len(ContentEncoding::Gzip, &gzip[..gzip.len() / 2]).is_err());
+
+        let mut deflate =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        deflate.write_all(body.as_bytes()).unwrap();
+        let deflate = deflate.finish().unwrap();
+        assert_eq!(
+            decoded_len(ContentEncoding::Deflate, &deflate).unwrap(),
+            body.len()
+        );
+
+        let mut br = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        br.write_all(body.as_bytes()).unwrap();
+        let br = br.into_inner();
+        assert_eq!(decoded_len(ContentEncoding::Br, &br).unwrap(), body.len());
+        assert!(decoded_len(ContentEncoding::Br, &br[..br.len() / 2]).is_err());
+
+        let zstd = zstd::encode_all(body.as_bytes(), 0).unwrap();
+        assert_eq!(
+            decoded_len(ContentEncoding::Zstd, &zstd).unwrap(),
+            body.len()
+        );
+        assert!(decoded_len(ContentEncoding::Zstd, &zstd[..zstd.len() / 2]).is_err());
+        // Concatenated frames are a valid stream
+        assert_eq!(
+            decoded_len(ContentEncoding::Zstd, &[zstd.as_slice(), &zstd].concat()).unwrap(),
+            body.len() * 2
+        );
+
+        assert_eq!(
+            decoded_len(ContentEncoding::Identity, body.as_bytes()).unwrap(),
+            body.len()
+        );
+        assert!(
+            Decoder::new(ContentEncoding::Other, false)
+                .unwrap()
+                .is_none()
+        );
+
+        let mut decoder = Decoder::new(ContentEncoding::Gzip, true).unwrap().unwrap();
+        decoder.write(&gzip).unwrap();
+        assert_eq!(decoder.finish().unwrap().body.unwrap(), body.as_bytes());
+    }
+
+    #[test]
+    fn test_content_encoding_from_headers() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!(
+            ContentEncoding::from_headers(&headers),
+            ContentEncoding::Identity
+        );
+        headers.insert(http::header::CONTENT_ENCODING, "GZIP".parse().unwrap());
+        assert_eq!(
+            ContentEncoding::from_headers(&headers),
+            ContentEncoding::Gzip
+        );
+        headers.insert(http::header::CONTENT_ENCODING, "gzip, br".parse().unwrap());
+        assert_eq!(
+            ContentEncoding::from_headers(&headers),
+            ContentEncoding::Other
+        );
+        headers.insert(http::header::CONTENT_ENCODING, "compress".parse().unwrap());
+        assert_eq!(
+            ContentEncoding::from_headers(&headers),
+            ContentEncoding::Other
+        );
+    }
+}
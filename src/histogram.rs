@@ -1,13 +1,82 @@
-pub fn histogram(values: &[f64], bins: usize) -> Vec<(f64, usize)> {
-    assert!(bins >= 2);
-    let mut bucket: Vec<usize> = vec![0; bins];
+/// How values are divided into the buckets of a histogram. Each bucket is labeled by its upper bound.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistogramBins {
+    /// Evenly spaced buckets between the min and max
+    Linear(usize),
+    /// Buckets between the min and max whose bounds grow geometrically
+    Log(usize),
+    /// A bucket up to each bound, and one up to the max for larger values
+    Boundaries(Vec<f64>),
+}
+
+impl HistogramBins {
+    /// Same kind of buckets but `n` of them. Explicit boundaries are kept.
+    pub fn with_len(&self, n: usize) -> Self {
+        match self {
+            HistogramBins::Linear(_) => HistogramBins::Linear(n),
+            HistogramBins::Log(_) => HistogramBins::Log(n),
+            HistogramBins::Boundaries(bounds) => HistogramBins::Boundaries(bounds.clone()),
+        }
+    }
+}
+
+impl Default for HistogramBins {
+    fn default() -> Self {
+        HistogramBins::Linear(11)
+    }
+}
+
+pub fn histogram(values: &[f64], bins: &HistogramBins) -> Vec<(f64, usize)> {
     let min = values.iter().collect::<average::Min>().min();
     let max = values.iter().collect::<average::Max>().max();
+    histogram_of_counts(values.iter().map(|&v| (v, 1)), min, max, bins)
+}
+
+/// Same as `histogram` but for values with their counts like ones from a HDR histogram. `min` and `max` are of the values.
+pub fn histogram_of_counts(
+    values: impl IntoIterator<Item = (f64, usize)>,
+    min: f64,
+    max: f64,
+    bins: &HistogramBins,
+) -> Vec<(f64, usize)> {
+    match bins {
+        // Log scale needs positive values
+        HistogramBins::Log(bins) if min > 0.0 && max > min => {
+            assert!(*bins >= 2);
+            let ratio = max / min;
+            let mut bounds: Vec<f64> = (0..*bins)
+                .map(|i| min * ratio.powf(i as f64 / (bins - 1) as f64))
+                .collect();
+            bounds[0] = min;
+            bounds[bins - 1] = max;
+            histogram_of_bounds(values, bounds)
+        }
+        HistogramBins::Linear(bins) | HistogramBins::Log(bins) => {
+            histogram_linear(values, min, max, *bins)
+        }
+        HistogramBins::Boundaries(bounds) => {
+            let mut bounds = bounds.clone();
+            if bounds.last().is_none_or(|&last| max > last) {
+                bounds.push(max);
+            }
+            histogram_of_bounds(values, bounds)
+        }
+    }
+}
+
+fn histogram_linear(
+    values: impl IntoIterator<Item = (f64, usize)>,
+    min: f64,
+    max: f64,
+    bins: usize,
+) -> Vec<(f64, usize)> {
+    assert!(bins >= 2);
+    let mut bucket: Vec<usize> = vec![0; bins];
     let step = (max - min) / (bins - 1) as f64;
 
-    for &v in values {
+    for (v, count) in values {
         let i = std::cmp::min(((v - min) / step).ceil() as usize, bins - 1);
-        bucket[i] += 1;
+        bucket[i] += count;
     }
 
     bucket
@@ -17,6 +86,21 @@ pub fn histogram(values: &[f64], bins: usize) -> Vec<(f64, usize)> {
         .collect()
 }
 
+/// Each value is counted in the first bucket whose bound is not less than it. `bounds` are sorted.
+fn histogram_of_bounds(
+    values: impl IntoIterator<Item = (f64, usize)>,
+    bounds: Vec<f64>,
+) -> Vec<(f64, usize)> {
+    let mut bucket: Vec<usize> = vec![0; bounds.len()];
+
+    for (v, count) in values {
+        let i = std::cmp::min(bounds.partition_point(|&b| b < v), bounds.len() - 1);
+        bucket[i] += count;
+    }
+
+    bounds.into_iter().zip(bucket).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,7 +109,7 @@ mod tests {
     fn test_histogram() {
         let values1: [f64; 10] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
         assert_eq!(
-            histogram(&values1, 10),
+            histogram(&values1, &HistogramBins::Linear(10)),
             vec![
                 (1.0, 1),
                 (2.0, 1),
@@ -40,11 +124,11 @@ mod tests {
             ]
         );
         assert_eq!(
-            histogram(&values1, 4),
+            histogram(&values1, &HistogramBins::Linear(4)),
             vec![(1.0, 1), (4.0, 3), (7.0, 3), (10.0, 3)]
         );
         assert_eq!(
-            histogram(&values1, 17),
+            histogram(&values1, &HistogramBins::Linear(17)),
             vec![
                 (1.0, 1),
                 (1.5625, 0),
@@ -68,7 +152,7 @@ mod tests {
 
         let values2: [f64; 10] = [1.0, 1.0, 1.0, 1.0, 1.0, 10.0, 10.0, 10.0, 10.0, 10.0];
         assert_eq!(
-            histogram(&values2, 10),
+            histogram(&values2, &HistogramBins::Linear(10)),
             vec![
                 (1.0, 5),
                 (2.0, 0),
@@ -82,6 +166,43 @@ mod tests {
                 (10.0, 5)
             ]
         );
-        assert_eq!(histogram(&values2, 2), vec![(1.0, 5), (10.0, 5)]);
+        assert_eq!(
+            histogram(&values2, &HistogramBins::Linear(2)),
+            vec![(1.0, 5), (10.0, 5)]
+        );
+    }
+
+    #[test]
+    fn test_histogram_log() {
+        let values: [f64; 5] = [0.001, 0.002, 0.01, 0.05, 0.1];
+        assert_eq!(
+            histogram(&values, &HistogramBins::Log(3)),
+            vec![(0.001, 1), (0.01, 2), (0.1, 2)]
+        );
+        // Falls back to linear without a positive min
+        assert_eq!(
+            histogram(&[0.0, 1.0, 2.0], &HistogramBins::Log(3)),
+            histogram(&[0.0, 1.0, 2.0], &HistogramBins::Linear(3))
+        );
+    }
+
+    #[test]
+    fn test_histogram_boundaries() {
+        let values: [f64; 5] = [0.001, 0.002, 0.01, 0.05, 0.1];
+        assert_eq!(
+            histogram(
+                &values,
+                &HistogramBins::Boundaries(vec![0.001, 0.005, 0.05])
+            ),
+            vec![(0.001, 1), (0.005, 1), (0.05, 2), (0.1, 1)]
+        );
+        assert_eq!(
+            histogram(&values, &HistogramBins::Boundaries(vec![0.01, 1.0])),
+            vec![(0.01, 3), (1.0, 2)]
+        );
+        assert_eq!(
+            histogram(&[], &HistogramBins::Boundaries(vec![0.01, 1.0])),
+            vec![(0.01, 0), (1.0, 0)]
+        );
     }
 }
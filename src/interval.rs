@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{
+    client::{ClientError, RequestResult},
+    result_data::{ResultData, StatsConfig},
+};
+
+/// Percentiles in a report
+const PERCENTILES: [f64; 3] = [50.0, 90.0, 99.0];
+
+/// Reports requests finished in each `--report-interval` while running
+pub struct IntervalReporter {
+    pub interval: Duration,
+    /// `--report-file` or stderr
+    pub output: Box<dyn Write + Send>,
+    /// A JSON object per line instead of a text line
+    pub json: bool,
+    /// When the run started
+    pub start: Instant,
+}
+
+#[derive(Serialize)]
+struct Report {
+    /// Seconds from the start to the end of the interval
+    elapsed: f64,
+    requests: usize,
+    rps: f64,
+    #[serde(rename = "latencyPercentiles")]
+    latency_percentiles: BTreeMap<String, f64>,
+    errors: usize,
+    #[serde(rename = "statusCodeDistribution")]
+    status_code_distribution: BTreeMap<String, usize>,
+    #[serde(rename = "errorDistribution")]
+    error_distribution: BTreeMap<String, usize>,
+}
+
+impl IntervalReporter {
+    /// Forwards results from `rx` to `tx` and reports each interval until `rx` is closed.
+    /// Results are forwarded even after failing to write a report, and the error is returned at last.
+    pub async fn run(
+        mut self,
+        rx: kanal::AsyncReceiver<Result<RequestResult, ClientError>>,
+        tx: kanal::Sender<Result<RequestResult, ClientError>>,
+    ) -> std::io::Result<()> {
+        let mut ticker = tokio::time::interval_at(
+            tokio::time::Instant::from_std(self.start + self.interval),
+            self.interval,
+        );
+        // A burst of catch-up ticks after a stall would report nearly empty windows
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut window = ResultData::default();
+        let mut window_start = self.start;
+        let mut write_result = Ok(());
+
+        loop {
+            tokio::select! {
+                res = rx.recv() => {
+                    let Ok(res) = res else {
+                        break;
+                    };
+                    window.record(&res);
+                    // The receiver is gone only by Ctrl-C
+                    let _ = tx.send(res);
+                }
+                _ = ticker.tick() => {
+                    let now = Instant::now();
+                    if write_result.is_ok() {
+                        write_result = self.report(&window, window_start, now);
+                    }
+                    window = ResultData::default();
+                    window_start = now;
+                }
+            }
+        }
+
+        if write_result.is_ok() && window.len() > 0 {
+            write_result = self.report(&window, window_start, Instant::now());
+        }
+        write_result
+    }
+
+    fn report(&mut self, window: &ResultData, from: Instant, to: Instant) -> std::io::Result<()> {
+        let percentiles = window
+            .duration_all_statistics(&StatsConfig {
+                percentiles: PERCENTILES.to_vec(),
+                ..Default::default()
+            })
+            .percentiles;
+        let errors = window.error_distribution().values().sum::<usize>();
+        let report = Report {
+            elapsed: (to - self.start).as_secs_f64(),
+            requests: window.len(),
+            rps: window.len() as f64 / (to - from).as_secs_f64(),
+            latency_percentiles: percentiles
+                .iter()
+                .map(|(p, v)| (format!("p{p}"), *v))
+                .collect(),
+            errors,
+            status_code_distribution: window
+                .status_code_distribution()
+                .into_iter()
+                .map(|(k, v)| (k.as_u16().to_string(), v))
+                .collect(),
+            error_distribution: window
+                .error_distribution()
+                .into_iter()
+                .map(|(kind, count)| (kind.as_str().to_string(), count))
+                .collect(),
+        };
+
+        if self.json {
+            serde_json::to_writer(&mut self.output, &report)?;
+            writeln!(self.output)?;
+        } else {
+            write!(
+                self.output,
+                "[{:.2}s] {} requests, {:.2} req/s",
+                report.elapsed, report.requests, report.rps
+            )?;
+            for (p, v) in percentiles {
+                write!(self.output, ", p{p} {v:.4} secs")?;
+            }
+            write!(self.output, ", {errors} errors")?;
+            for (status, count) in &report.status_code_distribution {
+                write!(self.output, ", [{status}] {count}")?;
+            }
+            writeln!(self.output)?;
+        }
+        self.output.flush()
+    }
+}
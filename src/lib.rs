@@ -3,6 +3,7 @@ use aws_auth::AwsSignatureConfig;
 use clap::Parser;
 use crossterm::tty::IsTty;
 use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use histogram::HistogramBins;
 use humantime::Duration;
 use hyper::{
     HeaderMap,
@@ -14,7 +15,7 @@ use hyper::{
 use printer::{PrintConfig, PrintMode};
 use rand_regex::Regex;
 use ratatui::crossterm;
-use result_data::ResultData;
+use result_data::{ResultData, ResultDataOptions, StatsConfig};
 use std::{
     env,
     fs::File,
@@ -28,16 +29,27 @@ use timescale::TimeScale;
 use url::Url;
 use url_generator::UrlGenerator;
 
+pub use slo::SloFailed;
+
+mod assertion;
 mod aws_auth;
 mod client;
+mod connection_stats;
+mod counting_stream;
 mod db;
+mod decompress;
 mod histogram;
+mod interval;
 mod monitor;
 mod pcg64si;
 mod printer;
 mod result_data;
+mod retry;
+mod slo;
 mod timescale;
 mod tls_config;
+#[cfg(feature = "rustls")]
+mod tls_stream;
 mod url_generator;
 
 #[cfg(not(target_env = "msvc"))]
@@ -152,6 +164,12 @@ Note: If qps is specified, burst will be ignored",
     proxy_headers: Vec<String>,
     #[arg(help = "Timeout for each request. Default to infinite.", short = 't')]
     timeout: Option<humantime::Duration>,
+    #[arg(
+        help = "Timeout for establishing each connection, covering TCP connect, TLS handshake and proxy CONNECT. DNS lookup isn't included.",
+        long = "connect-timeout",
+        default_value = "5s"
+    )]
+    connect_timeout: humantime::Duration,
     #[arg(help = "HTTP Accept Header.", short = 'A')]
     accept_header: Option<String>,
     #[arg(help = "HTTP request body.", short = 'd')]
@@ -195,6 +213,77 @@ Note: If qps is specified, burst will be ignored",
     host: Option<String>,
     #[arg(help = "Disable compression.", long = "disable-compression")]
     disable_compression: bool,
+    #[arg(
+        help = "Decode gzip, deflate, br and zstd response bodies and report decoded sizes per Content-Encoding. Decoding time is included in the response time. Accept-Encoding is set to the encodings that can be decoded.",
+        long = "decompress",
+        conflicts_with = "disable_compression"
+    )]
+    decompress: bool,
+    #[arg(
+        help = "Expected status codes of responses, comma separated. Classes like 2xx are allowed. Responses with other statuses are counted as errors. Examples: --assert-status 200,204 --assert-status 2xx",
+        long = "assert-status",
+        value_delimiter = ','
+    )]
+    assert_status: Vec<assertion::StatusPattern>,
+    #[arg(
+        help = "Regex the response body must match. Compressed bodies are matched as received unless --decompress is set.",
+        long = "assert-body-regex"
+    )]
+    assert_body_regex: Option<regex::bytes::Regex>,
+    #[arg(
+        help = "String the response body must contain. Can be specified multiple times.",
+        long = "assert-body-contains"
+    )]
+    assert_body_contains: Vec<String>,
+    #[arg(
+        help = "JSON pointer into the response body and the value it must equal, as pointer=value. Values which aren't valid JSON are compared as strings. Can be specified multiple times. Examples: --assert-json /status=ok --assert-json /items/0/id=1",
+        long = "assert-json"
+    )]
+    assert_json: Vec<assertion::JsonAssertion>,
+    #[arg(
+        help = "Header the response must have, optionally with its value. Can be specified multiple times. Examples: --assert-header x-request-id --assert-header \"content-type: application/json\"",
+        long = "assert-header"
+    )]
+    assert_header: Vec<assertion::HeaderAssertion>,
+    #[arg(
+        help = "Maximum size of the response body in bytes. The decoded size is checked with --decompress.",
+        long = "assert-max-body-size"
+    )]
+    assert_max_body_size: Option<usize>,
+    #[arg(
+        help = "SLO to check against the final results, as <metric><op><threshold>. Metrics are pN for response time percentiles, avg, max, success_rate, error_rate and rps. Ops are <, <=, > and >=. oha exits with code 3 if any of them fails. Can be specified multiple times. Examples: --assert \"p99<200ms\" --assert \"success_rate>=0.995\" --assert \"rps>1000\"",
+        long = "assert"
+    )]
+    slo: Vec<slo::SloAssertion>,
+    #[arg(
+        help = "Response header to record the value of, to show the distribution and latency per value. Values after the first 100 distinct ones are counted as (other). Can be specified multiple times. Examples: --track-header X-Cache --track-header X-Served-By",
+        long = "track-header"
+    )]
+    track_header: Vec<HeaderName>,
+    #[arg(
+        help = "Regex to group URLs matching it in the per-URL statistics, which are shown when this is specified. URLs matching none are shown by themselves. Can be specified multiple times and the first match wins. Example: --url-group '/users/[0-9]+'",
+        long = "url-group"
+    )]
+    url_group: Vec<regex::Regex>,
+    #[arg(
+        help = "Number of times to retry a request on the conditions of --retry-on. The response time of a retried request spans all of its attempts. -t applies to each attempt.",
+        long = "retries",
+        default_value = "0"
+    )]
+    retries: u32,
+    #[arg(
+        help = "Comma separated conditions to retry a request on. 'connection' is a failure to connect or of the connection, 'timeout' is the timeout of -t and a status code is a response with it.",
+        long = "retry-on",
+        value_delimiter = ',',
+        default_value = "connection,timeout,502,503,504"
+    )]
+    retry_on: Vec<retry::RetryCondition>,
+    #[arg(
+        help = "Delay before the first retry, doubled for each following retry.",
+        long = "retry-backoff",
+        default_value = "100ms"
+    )]
+    retry_backoff: humantime::Duration,
     #[arg(
         help = "Limit for number of Redirect. Set 0 for no redirection. Redirection isn't supported for HTTP/2.",
         default_value = "10",
@@ -217,6 +306,33 @@ Note: If qps is specified, burst will be ignored",
     ipv6: bool,
     #[arg(help = "Lookup only ipv4.", long = "ipv4")]
     ipv4: bool,
+    #[arg(
+        help = "When to resolve the host again for new connections. 'ttl' honours the TTL of DNS records, 'connection' resolves for every connection and a duration like '5s' resolves on that interval regardless of TTL.",
+        long = "dns-refresh",
+        default_value = "ttl"
+    )]
+    dns_refresh: DnsRefresh,
+    #[arg(
+        help = "DNS server to use instead of the system's, as ip[:port]. Can be specified multiple times. Examples: --dns-server 127.0.0.1:5353 --dns-server [::1]",
+        long = "dns-server"
+    )]
+    dns_server: Vec<DnsServer>,
+    #[arg(
+        help = "Protocol to query --dns-server with. 'udp' falls back to TCP for truncated responses. 'tls' and 'https' require --dns-tls-name.",
+        long = "dns-protocol",
+        default_value = "udp"
+    )]
+    dns_protocol: DnsProtocol,
+    #[arg(
+        help = "Server name to verify the certificate of --dns-server against for DNS over TLS or HTTPS.",
+        long = "dns-tls-name"
+    )]
+    dns_tls_name: Option<String>,
+    #[arg(
+        help = "Look up hosts in the file before DNS. The format is the same as /etc/hosts.",
+        long = "hosts-file"
+    )]
+    hosts_file: Option<PathBuf>,
     #[arg(
         help = "(TLS) Use the specified certificate file to verify the peer. Native certificate store is used even if this argument is specified.",
         long
@@ -234,30 +350,65 @@ Note: If qps is specified, burst will be ignored",
     key: Option<PathBuf>,
     #[arg(help = "Accept invalid certs.", long = "insecure")]
     insecure: bool,
+    #[arg(help = "(TLS) Minimum TLS version to negotiate.", long = "tls-min")]
+    tls_min: Option<tls_config::TlsVersion>,
+    #[arg(help = "(TLS) Maximum TLS version to negotiate.", long = "tls-max")]
+    tls_max: Option<tls_config::TlsVersion>,
+    #[arg(
+        help = "(TLS) Comma separated allowlist of cipher suites in preference order. Examples: --tls-ciphers TLS13_AES_128_GCM_SHA256,TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256
+Not supported with native-tls.",
+        long = "tls-ciphers",
+        value_delimiter = ','
+    )]
+    tls_ciphers: Vec<String>,
+    #[arg(
+        help = "(TLS) Server name to send in SNI and to verify the certificate against. Defaults to the host of the URL. It's independent of --connect-to.",
+        long = "sni"
+    )]
+    sni: Option<String>,
+    #[arg(
+        help = "(TLS) Comma separated ALPN protocols to offer. Examples: --alpn h2,http/1.1
+Defaults to h2 for HTTP/2 and nothing for HTTP/1.",
+        long = "alpn",
+        value_delimiter = ','
+    )]
+    alpn: Option<Vec<String>>,
+    #[arg(
+        help = "(TLS) Session resumption mode. 'off' makes every connection perform a full handshake. 'early-data' sends requests as TLS 1.3 0-RTT early data, only allowed for idempotent methods.
+Not supported with native-tls.",
+        long = "tls-resumption",
+        default_value = "on"
+    )]
+    tls_resumption: tls_config::TlsResumption,
     #[arg(
         help = "Override DNS resolution and default port numbers with strings like 'example.org:443:localhost:8443'
 Note: if used several times for the same host:port:target_host:target_port, a random choice is made",
         long = "connect-to"
     )]
     connect_to: Vec<ConnectToEntry>,
+    #[arg(
+        help = "Local IP address to bind outgoing TCP connections to. Can be specified multiple times to spread connections across them randomly. Examples: --bind-address 10.0.0.1 --bind-address 10.0.0.2",
+        long = "bind-address"
+    )]
+    bind_address: Vec<std::net::IpAddr>,
     #[arg(help = "Disable the color scheme.", long = "disable-color")]
     disable_color: bool,
     #[cfg(unix)]
     #[arg(
-        help = "Connect to a unix socket instead of the domain in the URL. Only for non-HTTPS URLs.",
+        help = "Connect to a unix socket instead of the domain in the URL. For HTTPS URLs, TLS is used over the socket with the host of the URL for SNI.",
         long = "unix-socket",
         group = "socket-type"
     )]
     unix_socket: Option<std::path::PathBuf>,
     #[cfg(feature = "vsock")]
     #[arg(
-        help = "Connect to a VSOCK socket using 'cid:port' instead of the domain in the URL. Only for non-HTTPS URLs.",
+        help = "Connect to a VSOCK socket using 'cid:port' instead of the domain in the URL. For HTTPS URLs, TLS is used over the socket with the host of the URL for SNI.",
         long = "vsock-addr",
         group = "socket-type"
     )]
     vsock_addr: Option<VsockAddr>,
     #[arg(
-        help = "Include a response status code successful or not successful breakdown for the time histogram and distribution statistics",
+        help = "Include a response status code successful or not successful breakdown for the time histogram and distribution statistics, and the statistics for each status code and the time to failure for each error",
         long = "stats-success-breakdown"
     )]
     stats_success_breakdown: bool,
@@ -285,6 +436,51 @@ Note: if used several times for the same host:port:target_host:target_port, a ra
         short = 'u'
     )]
     time_unit: Option<TimeScale>,
+    #[arg(
+        help = "Comma separated percentiles of the latency distributions. Examples: --percentiles 50,90,99,99.9,99.999",
+        long = "percentiles",
+        value_delimiter = ',',
+        value_parser = parse_percentile,
+        default_value = "10,25,50,75,90,95,99,99.9,99.99"
+    )]
+    percentiles: Vec<f64>,
+    #[arg(
+        help = "Number of buckets of the response time histograms.",
+        long = "histogram-bins",
+        value_parser = clap::value_parser!(u64).range(2..),
+        default_value = "11"
+    )]
+    histogram_bins: u64,
+    #[arg(
+        help = "Scale of the response time histogram buckets. 'log' makes buckets of fast responses narrower than ones of slow responses.",
+        long = "histogram-scale",
+        default_value = "linear"
+    )]
+    histogram_scale: HistogramScale,
+    #[arg(
+        help = "Comma separated upper bounds of the response time histogram buckets, instead of --histogram-bins and --histogram-scale. Slower responses are in the last bucket up to the slowest. Examples: --histogram-boundaries 1ms,5ms,10ms,50ms,100ms",
+        long = "histogram-boundaries",
+        value_delimiter = ',',
+        conflicts_with_all = ["histogram_bins", "histogram_scale"]
+    )]
+    histogram_boundaries: Vec<humantime::Duration>,
+    #[arg(
+        help = "Report requests finished in each interval while running, with requests per second, p50/p90/p99 latency, errors and status codes. It's a JSON object per line with --output-format json, otherwise a text line. Examples: --report-interval 10s",
+        long = "report-interval"
+    )]
+    report_interval: Option<humantime::Duration>,
+    #[arg(
+        help = "File to write --report-interval reports to. If not specified, they are written to stderr.",
+        long = "report-file",
+        requires = "report_interval"
+    )]
+    report_file: Option<PathBuf>,
+    #[arg(
+        help = "Width of the buckets of the time series in the JSON output, which has the requests, errors, bytes and latency of the responses ended in each bucket.",
+        long = "time-series-interval",
+        default_value = "1s"
+    )]
+    time_series_interval: humantime::Duration,
 }
 
 /// An entry specified by `connect-to` to override DNS resolution and default
@@ -327,6 +523,79 @@ impl FromStr for ConnectToEntry {
     }
 }
 
+/// How often DNS lookups are done again, specified by `--dns-refresh`
+#[derive(Clone, Copy, Debug)]
+pub enum DnsRefresh {
+    /// Cache records until their TTL expires
+    Ttl,
+    /// Don't cache records
+    Connection,
+    /// Cache records for the fixed duration
+    Interval(std::time::Duration),
+}
+
+impl FromStr for DnsRefresh {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ttl" => Ok(Self::Ttl),
+            "connection" => Ok(Self::Connection),
+            _ => humantime::parse_duration(s)
+                .map(Self::Interval)
+                .map_err(|err| {
+                    format!(
+                        "--dns-refresh must be ttl, connection or a duration, but got {s}: {err}"
+                    )
+                }),
+        }
+    }
+}
+
+/// A DNS server specified by `--dns-server`. The port defaults to the one of `--dns-protocol`.
+#[derive(Clone, Copy, Debug)]
+pub struct DnsServer {
+    pub ip: std::net::IpAddr,
+    pub port: Option<u16>,
+}
+
+impl FromStr for DnsServer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(addr) = s.parse::<std::net::SocketAddr>() {
+            return Ok(Self {
+                ip: addr.ip(),
+                port: Some(addr.port()),
+            });
+        }
+        let ip = s
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .unwrap_or(s);
+        Ok(Self {
+            ip: ip.parse().map_err(|err| {
+                format!("syntax for --dns-server is ip[:port], but got {s}: {err}")
+            })?,
+            port: None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum HistogramScale {
+    Linear,
+    Log,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum DnsProtocol {
+    Udp,
+    Tcp,
+    Tls,
+    Https,
+}
+
 /// A wrapper around a [`tokio_vsock::VsockAddr`] that provides a parser for clap
 #[derive(Debug, Clone)]
 #[repr(transparent)]
@@ -445,7 +714,12 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         );
 
         // https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Accept-Encoding
-        if !opts.disable_compression {
+        if opts.decompress {
+            headers.insert(
+                http::header::ACCEPT_ENCODING,
+                http::header::HeaderValue::from_static("gzip, deflate, br, zstd"),
+            );
+        } else if !opts.disable_compression {
             headers.insert(
                 http::header::ACCEPT_ENCODING,
                 http::header::HeaderValue::from_static("gzip, compress, deflate, br"),
@@ -542,6 +816,18 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         _ => None,
     };
 
+    // Fail early rather than every request failing with ClientError::NoBindAddress
+    if !opts.bind_address.is_empty() {
+        anyhow::ensure!(
+            !opts.ipv4 || opts.ipv6 || opts.bind_address.iter().any(|a| a.is_ipv4()),
+            "--ipv4 requires an IPv4 --bind-address"
+        );
+        anyhow::ensure!(
+            !opts.ipv6 || opts.ipv4 || opts.bind_address.iter().any(|a| a.is_ipv6()),
+            "--ipv6 requires an IPv6 --bind-address"
+        );
+    }
+
     let ip_strategy = match (opts.ipv4, opts.ipv6) {
         (false, false) => Default::default(),
         (true, false) => hickory_resolver::config::LookupIpStrategy::Ipv4Only,
@@ -549,13 +835,42 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         (true, true) => hickory_resolver::config::LookupIpStrategy::Ipv4AndIpv6,
     };
     let (config, mut resolver_opts) = system_resolv_conf()?;
+    let config = if opts.dns_server.is_empty() {
+        anyhow::ensure!(
+            opts.dns_protocol == DnsProtocol::Udp,
+            "--dns-protocol requires --dns-server"
+        );
+        config
+    } else {
+        custom_resolv_conf(&opts.dns_server, opts.dns_protocol, opts.dns_tls_name)?
+    };
+    #[cfg(feature = "rustls")]
+    if matches!(opts.dns_protocol, DnsProtocol::Tls | DnsProtocol::Https) {
+        resolver_opts.tls_config = tls_config::dns_tls_config(opts.cacert.as_deref())?;
+    }
     resolver_opts.ip_strategy = ip_strategy;
+    match opts.dns_refresh {
+        DnsRefresh::Ttl => {}
+        DnsRefresh::Connection => resolver_opts.cache_size = 0,
+        DnsRefresh::Interval(interval) => {
+            resolver_opts.positive_min_ttl = Some(interval);
+            resolver_opts.positive_max_ttl = Some(interval);
+        }
+    }
     let resolver = hickory_resolver::Resolver::builder_with_config(
         config,
         hickory_resolver::name_server::TokioConnectionProvider::default(),
     )
     .with_options(resolver_opts)
     .build();
+    let hosts = if let Some(hosts_file) = &opts.hosts_file {
+        client::parse_hosts(
+            &std::fs::read_to_string(hosts_file)
+                .with_context(|| format!("failed to read {}", hosts_file.display()))?,
+        )
+    } else {
+        Default::default()
+    };
     let cacert = opts.cacert.as_deref().map(std::fs::read).transpose()?;
     let client_auth = match (opts.cert, opts.key) {
         (Some(cert), Some(key)) => Some((std::fs::read(cert)?, std::fs::read(key)?)),
@@ -563,6 +878,21 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         // TODO: Ensure it on clap
         _ => anyhow::bail!("Both --cert and --key must be specified"),
     };
+    let tls_options = tls_config::TlsOptions {
+        min_version: opts.tls_min,
+        max_version: opts.tls_max,
+        cipher_suites: opts.tls_ciphers,
+        alpn: opts.alpn,
+        resumption: opts.tls_resumption,
+    };
+    // 0-RTT data can be replayed by an attacker
+    anyhow::ensure!(
+        opts.tls_resumption != tls_config::TlsResumption::EarlyData || opts.method.is_idempotent(),
+        "--tls-resumption early-data is only allowed for idempotent methods"
+    );
+
+    // Opt-in because every distinct URL has its own histogram
+    let url_groups = (!opts.url_group.is_empty()).then_some(opts.url_group);
 
     let client = Arc::new(client::Client {
         aws_config,
@@ -576,11 +906,33 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         dns: client::Dns {
             resolver,
             connect_to: opts.connect_to,
+            resolved: Default::default(),
+            hosts,
         },
         timeout: opts.timeout.map(|d| d.into()),
+        connect_timeout: opts.connect_timeout.into(),
+        bind_addresses: opts.bind_address,
         redirect_limit: opts.redirect,
         disable_keepalive: opts.disable_keepalive,
+        decompress: opts.decompress,
+        assertions: assertion::Assertions {
+            status: opts.assert_status,
+            body_regex: opts.assert_body_regex,
+            body_contains: opts.assert_body_contains,
+            json: opts.assert_json,
+            headers: opts.assert_header,
+            max_body_size: opts.assert_max_body_size,
+        },
+        track_headers: opts.track_header,
+        url_groups,
+        retry: retry::RetryPolicy {
+            retries: opts.retries,
+            conditions: opts.retry_on,
+            backoff: opts.retry_backoff.into(),
+        },
+        connections: Default::default(),
         proxy_url: opts.proxy,
+        sni: opts.sni,
         #[cfg(unix)]
         unix_socket: opts.unix_socket,
         #[cfg(feature = "vsock")]
@@ -592,7 +944,8 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
             client_auth
                 .as_ref()
                 .map(|(cert, key)| (cert.as_slice(), key.as_slice())),
-        ),
+            &tls_options,
+        )?,
         #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
         native_tls_connectors: tls_config::NativeTlsConnectors::new(
             opts.insecure,
@@ -600,7 +953,8 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
             client_auth
                 .as_ref()
                 .map(|(cert, key)| (cert.as_slice(), key.as_slice())),
-        ),
+            &tls_options,
+        )?,
     });
 
     if !opts.no_pre_lookup {
@@ -627,6 +981,33 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
             disable_style,
             stats_success_breakdown: opts.stats_success_breakdown,
             time_unit: opts.time_unit,
+            slo: opts.slo.clone(),
+            stats: {
+                let mut percentiles = opts.percentiles;
+                percentiles.sort_by(f64::total_cmp);
+                percentiles.dedup();
+                let histogram_bins = if opts.histogram_boundaries.is_empty() {
+                    match opts.histogram_scale {
+                        HistogramScale::Linear => {
+                            HistogramBins::Linear(opts.histogram_bins as usize)
+                        }
+                        HistogramScale::Log => HistogramBins::Log(opts.histogram_bins as usize),
+                    }
+                } else {
+                    let mut bounds: Vec<f64> = opts
+                        .histogram_boundaries
+                        .into_iter()
+                        .map(|d| std::time::Duration::from(d).as_secs_f64())
+                        .collect();
+                    bounds.sort_by(f64::total_cmp);
+                    bounds.dedup();
+                    HistogramBins::Boundaries(bounds)
+                };
+                StatsConfig {
+                    percentiles,
+                    histogram_bins,
+                }
+            },
         }
     };
 
@@ -634,7 +1015,34 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         .duration_since(std::time::UNIX_EPOCH)?
         .as_secs();
     let start = std::time::Instant::now();
+    anyhow::ensure!(
+        !opts.time_series_interval.is_zero(),
+        "--time-series-interval must be longer than 0"
+    );
+    let result_options = ResultDataOptions {
+        // CSV and SQLite outputs need every result
+        keep_results: matches!(opts.output_format, Some(PrintMode::Csv)) || opts.db_url.is_some(),
+        time_series: matches!(opts.output_format, Some(PrintMode::Json))
+            .then(|| (start, opts.time_series_interval.into())),
+    };
+    if let Some((start, interval)) = result_options.time_series {
+        client.connections.set_time_series(start, interval);
+    }
+    let interval_reporter = match opts.report_interval {
+        Some(interval) => Some(interval::IntervalReporter {
+            interval: interval.into(),
+            output: if let Some(report_file) = &opts.report_file {
+                Box::new(File::create(report_file)?)
+            } else {
+                Box::new(std::io::stderr())
+            },
+            json: matches!(opts.output_format, Some(PrintMode::Json)),
+            start,
+        }),
+        None => None,
+    };
 
+    let mut interval_reporter_handle = None;
     let data_collect_future: Pin<Box<dyn std::future::Future<Output = (ResultData, PrintConfig)>>> =
         match work_mode {
             WorkMode::Debug => {
@@ -648,7 +1056,7 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                 n_http2_parallel,
                 query_limit: None,
                 latency_correction: _,
-            } if no_tui => {
+            } if no_tui && interval_reporter.is_none() => {
                 // Use optimized worker of no_tui mode.
                 let (result_tx, result_rx) = kanal::unbounded();
 
@@ -658,11 +1066,12 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                     n_requests,
                     n_connections,
                     n_http2_parallel,
+                    result_options,
                 )
                 .await;
 
                 Box::pin(async move {
-                    let mut res = ResultData::default();
+                    let mut res = ResultData::new(result_options);
                     for r in result_rx {
                         res.merge(r);
                     }
@@ -676,7 +1085,7 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                 query_limit: None,
                 latency_correction: _,
                 wait_ongoing_requests_after_deadline,
-            } if no_tui => {
+            } if no_tui && interval_reporter.is_none() => {
                 // Use optimized worker of no_tui mode.
                 let (result_tx, result_rx) = kanal::unbounded();
 
@@ -687,11 +1096,12 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                     n_connections,
                     n_http2_parallel,
                     wait_ongoing_requests_after_deadline,
+                    result_options,
                 )
                 .await;
 
                 Box::pin(async move {
-                    let mut res = ResultData::default();
+                    let mut res = ResultData::new(result_options);
                     for r in result_rx {
                         res.merge(r);
                     }
@@ -700,23 +1110,37 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
             }
             mode => {
                 let (result_tx, result_rx) = kanal::unbounded();
+                let result_rx = if let Some(interval_reporter) = interval_reporter {
+                    // Reports while passing results to the collector below
+                    let (tx, rx) = kanal::unbounded();
+                    interval_reporter_handle = Some(tokio::spawn(
+                        interval_reporter.run(result_rx.to_async(), tx),
+                    ));
+                    rx
+                } else {
+                    result_rx
+                };
                 let data_collector = if no_tui {
                     // When `--no-tui` is enabled, just collect all data.
 
                     let token = tokio_util::sync::CancellationToken::new();
                     let result_rx_ctrl_c = result_rx.clone();
                     let token_ctrl_c = token.clone();
+                    let connections = client.connections.clone();
                     let ctrl_c = tokio::spawn(async move {
                         tokio::select! {
                             _ = tokio::signal::ctrl_c() => {
-                                let mut all: ResultData = Default::default();
+                                let mut all = ResultData::new(result_options);
                                 let mut buf = Vec::new();
                                 let _ = result_rx_ctrl_c.drain_into(&mut buf);
                                 for res in buf {
                                     all.push(res);
                                 }
-                                let _ = printer::print_result(print_config, start, &all, start.elapsed());
-                                std::process::exit(libc::EXIT_SUCCESS);
+                                all.set_connection_stat(connections.stat());
+                                let duration = start.elapsed();
+                                let slo = print_config.slo.clone();
+                                let _ = printer::print_result(print_config, start, &all, duration);
+                                slo::exit_interrupted(&slo, &all, duration);
                             }
                             _ = token_ctrl_c.cancelled() => {
                                 print_config
@@ -728,7 +1152,7 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                     Box::pin(async move {
                         token.cancel();
                         let config = ctrl_c.await.unwrap();
-                        let mut all = ResultData::default();
+                        let mut all = ResultData::new(result_options);
                         while let Ok(res) = result_rx.recv() {
                             all.push(res);
                         }
@@ -749,6 +1173,9 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                             fps: opts.fps,
                             disable_color: opts.disable_color,
                             time_unit: opts.time_unit,
+                            result_options,
+                            url_breakdown: client.url_groups.is_some(),
+                            connections: client.connections.clone(),
                         }
                         .monitor(),
                     );
@@ -768,7 +1195,7 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                     } => {
                         if let Some(query_limit) = query_limit {
                             if latency_correction {
-                                client::work_with_qps(
+                                client::work_with_qps_latency_correction(
                                     client.clone(),
                                     result_tx,
                                     query_limit,
@@ -778,7 +1205,7 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
                                 )
                                 .await;
                             } else {
-                                client::work_with_qps_latency_correction(
+                                client::work_with_qps(
                                     client.clone(),
                                     result_tx,
                                     query_limit,
@@ -852,13 +1279,37 @@ pub async fn run(mut opts: Opts) -> anyhow::Result<()> {
         };
 
     let duration = start.elapsed();
-    let (res, print_config) = data_collect_future.await;
+    let (mut res, print_config) = data_collect_future.await;
+    res.set_connection_stat(client.connections.stat());
+
+    if let Some(handle) = interval_reporter_handle {
+        handle.await??;
+    }
 
     printer::print_result(print_config, start, &res, duration)?;
 
     if let Some(db_url) = opts.db_url {
         eprintln!("Storing results to {db_url}");
-        db::store(&client, &db_url, start, res.success(), run)?;
+        db::store(
+            &client,
+            &db_url,
+            start,
+            res.success(),
+            res.error_kinds(),
+            run,
+        )?;
+    }
+
+    let failed = slo::evaluate(&opts.slo, &res, duration)
+        .iter()
+        .filter(|result| !result.passed)
+        .count();
+    if failed > 0 {
+        return Err(SloFailed {
+            failed,
+            total: opts.slo.len(),
+        }
+        .into());
     }
 
     Ok(())
@@ -879,6 +1330,46 @@ fn system_resolv_conf() -> anyhow::Result<(ResolverConfig, ResolverOpts)> {
         .context("DNS: failed to load /etc/resolv.conf")
 }
 
+fn custom_resolv_conf(
+    servers: &[DnsServer],
+    protocol: DnsProtocol,
+    tls_name: Option<String>,
+) -> anyhow::Result<ResolverConfig> {
+    use hickory_resolver::{config::NameServerConfig, proto::xfer::Protocol};
+
+    let (protocols, default_port): (&[Protocol], u16) = match protocol {
+        DnsProtocol::Udp => (&[Protocol::Udp, Protocol::Tcp], 53),
+        DnsProtocol::Tcp => (&[Protocol::Tcp], 53),
+        #[cfg(feature = "rustls")]
+        DnsProtocol::Tls => (&[Protocol::Tls], 853),
+        #[cfg(feature = "rustls")]
+        DnsProtocol::Https => (&[Protocol::Https], 443),
+        #[cfg(not(feature = "rustls"))]
+        DnsProtocol::Tls | DnsProtocol::Https => {
+            anyhow::bail!("DNS over TLS or HTTPS isn't supported with native-tls")
+        }
+    };
+    let tls_name = match protocol {
+        DnsProtocol::Udp | DnsProtocol::Tcp => None,
+        DnsProtocol::Tls | DnsProtocol::Https => {
+            Some(tls_name.context("--dns-tls-name is required for DNS over TLS or HTTPS")?)
+        }
+    };
+
+    let mut name_servers = Vec::new();
+    for server in servers {
+        for &protocol in protocols {
+            let mut config = NameServerConfig::new(
+                std::net::SocketAddr::new(server.ip, server.port.unwrap_or(default_port)),
+                protocol,
+            );
+            config.tls_dns_name.clone_from(&tls_name);
+            name_servers.push(config);
+        }
+    }
+    Ok(ResolverConfig::from_parts(None, Vec::new(), name_servers))
+}
+
 enum WorkMode {
     Debug,
     FixedNumber {
@@ -941,6 +1432,17 @@ impl Opts {
     }
 }
 
+fn parse_percentile(s: &str) -> Result<f64, String> {
+    let p: f64 = s
+        .parse()
+        .map_err(|err| format!("invalid percentile {s}: {err}"))?;
+    if p > 0.0 && p <= 100.0 {
+        Ok(p)
+    } else {
+        Err(format!("percentile must be in (0, 100], but got {s}"))
+    }
+}
+
 fn parse_header(s: &str) -> Result<(HeaderName, HeaderValue), anyhow::Error> {
     let header = s.splitn(2, ':').collect::<Vec<_>>();
     anyhow::ensure!(header.len() == 2, anyhow::anyhow!("Parse header"));
@@ -1,5 +1,5 @@
 use clap::Parser;
-use oha::{Opts, run};
+use oha::{Opts, SloFailed, run};
 
 fn main() {
     let num_workers_threads = std::env::var("TOKIO_WORKER_THREADS")
@@ -16,6 +16,10 @@ fn main() {
 
     if let Err(e) = rt.block_on(run(Opts::parse())) {
         eprintln!("Error: {}", e);
-        std::process::exit(libc::EXIT_FAILURE);
+        std::process::exit(if e.is::<SloFailed>() {
+            SloFailed::EXIT_CODE
+        } else {
+            libc::EXIT_FAILURE
+        });
     }
 }
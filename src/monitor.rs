@@ -1,3 +1,4 @@
+use average::Mean;
 use byte_unit::Byte;
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 use hyper::http;
@@ -8,12 +9,16 @@ use ratatui::{
     text::{Line, Span},
     widgets::{BarChart, Block, Borders, Gauge, Paragraph},
 };
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
 
 use crate::{
     client::{ClientError, RequestResult},
-    printer::PrintConfig,
-    result_data::{MinMaxMean, ResultData},
+    connection_stats::ConnectionRegistry,
+    printer::{PrintConfig, header_value_label},
+    result_data::{MinMaxMean, ResultData, ResultDataOptions, UrlStat},
     timescale::{TimeLabel, TimeScale},
 };
 
@@ -47,6 +52,48 @@ impl ColorScheme {
     }
 }
 
+/// Column to sort the URL distribution by, in descending order
+#[derive(Clone, Copy)]
+enum UrlSort {
+    Count,
+    ErrorRate,
+    P50,
+    P95,
+    P99,
+}
+
+impl UrlSort {
+    fn next(self) -> Self {
+        match self {
+            Self::Count => Self::ErrorRate,
+            Self::ErrorRate => Self::P50,
+            Self::P50 => Self::P95,
+            Self::P95 => Self::P99,
+            Self::P99 => Self::Count,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::ErrorRate => "error rate",
+            Self::P50 => "p50",
+            Self::P95 => "p95",
+            Self::P99 => "p99",
+        }
+    }
+}
+
+/// A successful result to draw
+struct Recent {
+    end: std::time::Instant,
+    duration: f64,
+    /// None if the body is empty
+    ttfb: Option<f64>,
+    download_time: Option<f64>,
+    len_bytes: usize,
+}
+
 pub struct Monitor {
     pub print_config: PrintConfig,
     pub end_line: EndLine,
@@ -58,6 +105,12 @@ pub struct Monitor {
     pub fps: usize,
     pub disable_color: bool,
     pub time_unit: Option<TimeScale>,
+    /// Passed to `ResultData::new`
+    pub result_options: ResultDataOptions,
+    /// Whether results have the per-URL statistics to view
+    pub url_breakdown: bool,
+    /// For the connection stats printed on Ctrl-C
+    pub connections: Arc<ConnectionRegistry>,
 }
 
 struct IntoRawMode;
@@ -80,8 +133,9 @@ impl Monitor {
         let (raw_mode, mut terminal) = IntoRawMode::new()?;
 
         // Return this when ends to application print summary
-        // We must not read all data from this due to computational cost.
-        let mut all: ResultData = Default::default();
+        let mut all = ResultData::new(self.result_options);
+        // Results in the range of the chart, in the order of arrival
+        let mut recent: VecDeque<Recent> = VecDeque::new();
         // stats for HTTP status
         let mut status_dist: BTreeMap<http::StatusCode, usize> = Default::default();
 
@@ -92,6 +146,10 @@ impl Monitor {
         // None means auto timescale which depends on how long it takes
         let mut timescale_auto = self.time_unit;
 
+        // Whether the URL distribution is viewed instead of the charts
+        let mut url_view = false;
+        let mut url_sort = UrlSort::Count;
+
         let mut colors = ColorScheme::new();
         if !self.disable_color {
             colors.set_colors();
@@ -105,6 +163,13 @@ impl Monitor {
             let _ = self.report_receiver.drain_into(&mut buf);
             for report in buf.drain(..) {
                 if let Ok(report) = report.as_ref() {
+                    recent.push_back(Recent {
+                        end: report.end,
+                        duration: report.duration().as_secs_f64(),
+                        ttfb: report.ttfb().map(|d| d.as_secs_f64()),
+                        download_time: report.download_time().map(|d| d.as_secs_f64()),
+                        len_bytes: report.len_bytes,
+                    });
                     *status_dist.entry(report.status).or_default() += 1;
                 }
                 all.push(report);
@@ -134,9 +199,18 @@ impl Monitor {
 
             let bin = timescale.as_secs_f64();
 
+            // Older ones are out of the chart. They don't come back by zooming out.
+            while recent
+                .front()
+                .is_some_and(|r| (now - r.end).as_secs_f64() > bin * (count + 1) as f64)
+            {
+                recent.pop_front();
+            }
+            let recent = recent.make_contiguous();
+
             let mut bar_num_req = vec![0u64; count];
             let short_bin = (now - self.start).as_secs_f64() % bin;
-            for r in all.success().iter().rev() {
+            for r in recent.iter().rev() {
                 let past = (now - r.end).as_secs_f64();
                 let i = if past <= short_bin {
                     0
@@ -180,14 +254,16 @@ impl Monitor {
             #[cfg(unix)]
             let nofile = std::fs::read_dir("/dev/fd").map(|dir| dir.count());
 
+            let header_dist = all.header_value_distribution(&self.print_config.stats.percentiles);
+
             terminal.draw(|f| {
                 let row4 = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints(
                         [
                             Constraint::Length(3),
-                            Constraint::Length(8),
-                            Constraint::Length(all.error_distribution().len() as u16 + 2),
+                            Constraint::Length(10),
+                            Constraint::Length(all.error_kinds().len() as u16 + 2),
                             Constraint::Fill(1),
                         ]
                         .as_ref(),
@@ -196,7 +272,16 @@ impl Monitor {
 
                 let mid = Layout::default()
                     .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                    .constraints(if header_dist.is_empty() {
+                        [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref()
+                    } else {
+                        [
+                            Constraint::Percentage(34),
+                            Constraint::Percentage(33),
+                            Constraint::Percentage(33),
+                        ]
+                        .as_ref()
+                    })
                     .split(row4[1]);
 
                 let bottom = Layout::default()
@@ -222,8 +307,7 @@ impl Monitor {
                 f.render_widget(gauge, row4[0]);
 
                 let last_1_timescale = {
-                    let success = all.success();
-                    let index = match success.binary_search_by(|probe| {
+                    let index = match recent.binary_search_by(|probe| {
                         (now - probe.end)
                             .as_secs_f64()
                             .partial_cmp(&timescale.as_secs_f64())
@@ -235,12 +319,15 @@ impl Monitor {
                         Err(i) => i,
                     };
 
-                    &success[index..]
+                    &recent[index..]
                 };
 
-                let last_1_minmaxmean: MinMaxMean = last_1_timescale
+                let last_1_minmaxmean: MinMaxMean =
+                    last_1_timescale.iter().map(|r| r.duration).collect();
+                let last_1_ttfb: Mean = last_1_timescale.iter().filter_map(|r| r.ttfb).collect();
+                let last_1_download_time: Mean = last_1_timescale
                     .iter()
-                    .map(|r| r.duration().as_secs_f64())
+                    .filter_map(|r| r.download_time)
                     .collect();
 
                 let stats_text = vec![
@@ -257,6 +344,11 @@ impl Monitor {
                         format!("Average: {:.4} secs", last_1_minmaxmean.mean(),),
                         Style::default().fg(colors.light_blue.unwrap_or(Color::Reset)),
                     )]),
+                    Line::from(format!("Average TTFB: {:.4} secs", last_1_ttfb.mean())),
+                    Line::from(format!(
+                        "Average download: {:.4} secs",
+                        last_1_download_time.mean()
+                    )),
                     Line::from(format!(
                         "Data: {:.2}",
                         Byte::from_u64(
@@ -305,12 +397,40 @@ impl Monitor {
                 );
                 f.render_widget(stats2, mid[1]);
 
-                let mut error_v: Vec<(String, usize)> =
-                    all.error_distribution().clone().into_iter().collect();
-                error_v.sort_by_key(|t| std::cmp::Reverse(t.1));
+                if !header_dist.is_empty() {
+                    let headers_text = header_dist
+                        .into_iter()
+                        .flat_map(|(name, values)| {
+                            values.into_iter().map(move |(value, stat)| {
+                                let latency = stat
+                                    .percentiles
+                                    .iter()
+                                    .map(|(p, v)| format!("p{p} {v:.4}"))
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                Line::from(format!(
+                                    "{name}: {} [{}] {latency} secs",
+                                    header_value_label(value),
+                                    stat.count,
+                                ))
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    let headers = Paragraph::new(headers_text).block(
+                        Block::default()
+                            .title("Header distribution")
+                            .borders(Borders::ALL),
+                    );
+                    f.render_widget(headers, mid[2]);
+                }
+
+                let mut error_v = all.error_kinds().iter().collect::<Vec<_>>();
+                error_v.sort_by_key(|(_, stat)| std::cmp::Reverse(stat.count));
                 let errors_text = error_v
                     .into_iter()
-                    .map(|(e, count)| Line::from(format!("[{count}] {e}")))
+                    .map(|(kind, stat)| {
+                        Line::from(format!("[{}] {}: {}", stat.count, kind.as_str(), stat.sample))
+                    })
                     .collect::<Vec<_>>();
                 let errors = Paragraph::new(errors_text).block(
                     Block::default()
@@ -319,13 +439,60 @@ impl Monitor {
                 );
                 f.render_widget(errors, row4[2]);
 
+                if url_view {
+                    let mut url_v: Vec<(String, UrlStat)> =
+                        all.url_distribution().into_iter().collect();
+                    let percentile = |stat: &UrlStat, p: f64| {
+                        stat.percentiles
+                            .iter()
+                            .find(|(q, _)| *q == p)
+                            .map_or(f64::NAN, |(_, v)| *v)
+                    };
+                    let key = |stat: &UrlStat| match url_sort {
+                        UrlSort::Count => stat.count as f64,
+                        UrlSort::ErrorRate => stat.error_rate,
+                        UrlSort::P50 => percentile(stat, 50.0),
+                        UrlSort::P95 => percentile(stat, 95.0),
+                        UrlSort::P99 => percentile(stat, 99.0),
+                    };
+                    url_v.sort_by(|(_, a), (_, b)| key(b).total_cmp(&key(a)));
+                    let urls_text = url_v
+                        .into_iter()
+                        .map(|(url, stat)| {
+                            Line::from(format!(
+                                "[{}] {url} error {:.2}%, p50 {:.4} secs, p95 {:.4} secs, p99 {:.4} secs",
+                                stat.count,
+                                stat.error_rate * 100.0,
+                                percentile(&stat, 50.0),
+                                percentile(&stat, 95.0),
+                                percentile(&stat, 99.0)
+                            ))
+                        })
+                        .collect::<Vec<_>>();
+                    let urls = Paragraph::new(urls_text).block(
+                        Block::default()
+                            .title(format!(
+                                "URL distribution by {}. press s to sort, u to close",
+                                url_sort.label()
+                            ))
+                            .borders(Borders::ALL),
+                    );
+                    f.render_widget(urls, row4[3]);
+                    return;
+                }
+
                 let title = format!(
-                    "Requests / past {}{}. press -/+/a to change",
+                    "Requests / past {}{}. press -/+/a to change{}",
                     timescale,
                     if timescale_auto.is_none() {
                         " (auto)"
                     } else {
                         ""
+                    },
+                    if self.url_breakdown {
+                        ", u for URLs"
+                    } else {
+                        ""
                     }
                 );
 
@@ -361,10 +528,13 @@ impl Monitor {
                     .max(2);
                     let values = last_1_timescale
                         .iter()
-                        .map(|r| r.duration().as_secs_f64())
+                        .map(|r| r.duration)
                         .collect::<Vec<_>>();
 
-                    let histo = crate::histogram::histogram(&values, bins);
+                    let histo = crate::histogram::histogram(
+                        &values,
+                        &self.print_config.stats.histogram_bins.with_len(bins),
+                    );
                     histo
                         .into_iter()
                         .map(|(label, v)| (format!("{label:.4}"), v as u64))
@@ -415,6 +585,14 @@ impl Monitor {
                             timescale_auto = Some(timescale)
                         }
                     }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('u'),
+                        ..
+                    }) if self.url_breakdown => url_view = !url_view,
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('s'),
+                        ..
+                    }) if url_view => url_sort = url_sort.next(),
                     // User pressed q or ctrl-c
                     Event::Key(KeyEvent {
                         code: KeyCode::Char('q'),
@@ -427,13 +605,15 @@ impl Monitor {
                     }) => {
                         drop(terminal);
                         drop(raw_mode);
+                        all.set_connection_stat(self.connections.stat());
+                        let slo = self.print_config.slo.clone();
                         let _ = crate::printer::print_result(
                             self.print_config,
                             self.start,
                             &all,
                             now - self.start,
                         );
-                        std::process::exit(libc::EXIT_SUCCESS);
+                        crate::slo::exit_interrupted(&slo, &all, now - self.start);
                     }
                     _ => (),
                 }
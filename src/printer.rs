@@ -1,4 +1,8 @@
-use crate::{result_data::ResultData, timescale::TimeScale};
+use crate::{
+    result_data::{MinMaxMean, PERCENTILES, ResultData, Statistics, StatsConfig, TrackedValue},
+    slo::{self, SloAssertion},
+    timescale::TimeScale,
+};
 use average::{Max, Min, Variance};
 use byte_unit::Byte;
 use crossterm::style::{StyledContent, Stylize};
@@ -38,6 +42,17 @@ impl StyleScheme {
             self.no_style(text)
         }
     }
+    fn slo(self, text: &str, passed: bool) -> StyledContent<&str> {
+        if self.style_enabled {
+            if passed {
+                text.green().bold()
+            } else {
+                text.red().bold()
+            }
+        } else {
+            self.no_style(text)
+        }
+    }
     fn fastest(self, text: &str) -> StyledContent<&str> {
         if self.style_enabled {
             text.green()
@@ -109,6 +124,9 @@ pub struct PrintConfig {
     pub disable_style: bool,
     pub stats_success_breakdown: bool,
     pub time_unit: Option<TimeScale>,
+    pub stats: StatsConfig,
+    /// `--assert` SLOs to show the results of
+    pub slo: Vec<SloAssertion>,
 }
 
 pub fn print_result(
@@ -120,11 +138,14 @@ pub fn print_result(
     match config.mode {
         PrintMode::Text => print_summary(
             &mut config.output,
+            start,
             res,
             total_duration,
             config.disable_style,
             config.stats_success_breakdown,
             config.time_unit,
+            &config.stats,
+            &config.slo,
         )?,
         PrintMode::Json => print_json(
             &mut config.output,
@@ -132,6 +153,8 @@ pub fn print_result(
             res,
             total_duration,
             config.stats_success_breakdown,
+            &config.stats,
+            &config.slo,
         )?,
         PrintMode::Csv => print_csv(&mut config.output, start, res)?,
     }
@@ -145,12 +168,16 @@ fn print_json<W: Write>(
     res: &ResultData,
     total_duration: Duration,
     stats_success_breakdown: bool,
+    stats: &StatsConfig,
+    slo: &[SloAssertion],
 ) -> serde_json::Result<()> {
     use serde::Serialize;
     #[derive(Serialize)]
     struct Summary {
         #[serde(rename = "successRate")]
         success_rate: f64,
+        #[serde(rename = "assertionFailures")]
+        assertion_failures: usize,
         total: f64,
         slowest: f64,
         fastest: f64,
@@ -172,12 +199,205 @@ fn print_json<W: Write>(
         slowest: f64,
     }
 
+    impl From<MinMaxMean> for Triple {
+        fn from(stat: MinMaxMean) -> Self {
+            Self {
+                average: stat.mean(),
+                fastest: stat.min(),
+                slowest: stat.max(),
+            }
+        }
+    }
+
     #[derive(Serialize)]
     struct Details {
         #[serde(rename = "DNSDialup")]
         dns_dialup: Triple,
         #[serde(rename = "DNSLookup")]
         dns_lookup: Triple,
+        #[serde(rename = "TCPConnect")]
+        tcp_connect: Triple,
+        #[serde(rename = "TLSHandshake", skip_serializing_if = "Option::is_none")]
+        tls_handshake: Option<Triple>,
+        #[serde(rename = "ProxyConnect", skip_serializing_if = "Option::is_none")]
+        proxy_connect: Option<Triple>,
+    }
+
+    #[derive(Serialize)]
+    struct TlsResumption {
+        resumed: usize,
+        connections: usize,
+    }
+
+    #[derive(Serialize)]
+    struct Connections {
+        connections: usize,
+        #[serde(rename = "tlsConnections")]
+        tls_connections: usize,
+        reconnects: usize,
+        #[serde(rename = "redirectConnections")]
+        redirect_connections: usize,
+        #[serde(rename = "requestsPerConnection")]
+        requests_per_connection: BTreeMap<String, usize>,
+        #[serde(rename = "requestsPerConnectionPercentiles")]
+        requests_per_connection_percentiles: BTreeMap<String, usize>,
+        lifetime: Triple,
+    }
+
+    #[derive(Serialize)]
+    struct ErrorKind {
+        count: usize,
+        sample: String,
+        #[serde(rename = "firstSeen")]
+        first_seen: f64,
+        #[serde(rename = "lastSeen")]
+        last_seen: f64,
+    }
+
+    #[derive(Serialize)]
+    struct RemoteIp {
+        count: usize,
+        #[serde(rename = "firstSeen")]
+        first_seen: f64,
+        #[serde(rename = "lastSeen")]
+        last_seen: f64,
+    }
+
+    #[derive(Serialize)]
+    struct ContentEncoding {
+        count: usize,
+        #[serde(rename = "wireBytes")]
+        wire_bytes: u64,
+        #[serde(rename = "decodedBytes")]
+        decoded_bytes: Option<u64>,
+        ratio: Option<f64>,
+    }
+
+    #[derive(Serialize)]
+    struct HeaderValue {
+        count: usize,
+        #[serde(rename = "latencyPercentiles")]
+        latency_percentiles: BTreeMap<String, f64>,
+    }
+
+    #[derive(Serialize)]
+    struct UrlGroup {
+        count: usize,
+        #[serde(rename = "errorRate")]
+        error_rate: f64,
+        #[serde(rename = "latencyPercentiles")]
+        latency_percentiles: BTreeMap<String, f64>,
+    }
+
+    #[derive(Serialize)]
+    struct RedirectHop {
+        #[serde(rename = "statusCodeDistribution")]
+        status_code_distribution: BTreeMap<String, usize>,
+        latency: Triple,
+    }
+
+    #[derive(Serialize)]
+    struct Redirects {
+        #[serde(rename = "hopDistribution")]
+        hop_distribution: BTreeMap<String, usize>,
+        hops: Vec<RedirectHop>,
+    }
+
+    #[derive(Serialize)]
+    struct Retries {
+        #[serde(rename = "firstAttemptSuccessRate")]
+        first_attempt_success_rate: f64,
+        distribution: BTreeMap<String, usize>,
+    }
+
+    #[derive(Serialize)]
+    struct LatencyStatistics {
+        histogram: BTreeMap<String, usize>,
+        percentiles: BTreeMap<String, f64>,
+    }
+
+    impl From<Statistics> for LatencyStatistics {
+        fn from(statistics: Statistics) -> Self {
+            Self {
+                histogram: statistics
+                    .histogram
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect(),
+                percentiles: statistics
+                    .percentiles
+                    .into_iter()
+                    .map(|(p, v)| (format!("p{p}"), v))
+                    .collect(),
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct LatencyCorrection {
+        #[serde(rename = "serviceTime")]
+        service_time: Triple,
+        #[serde(rename = "serviceTimeHistogram")]
+        service_time_histogram: BTreeMap<String, usize>,
+        #[serde(rename = "serviceTimePercentiles")]
+        service_time_percentiles: BTreeMap<String, f64>,
+        #[serde(rename = "queueingDelay")]
+        queueing_delay: Triple,
+        #[serde(rename = "queueingDelayPercentiles")]
+        queueing_delay_percentiles: BTreeMap<String, f64>,
+    }
+
+    #[derive(Serialize)]
+    struct Throughput {
+        #[serde(rename = "bytesSent")]
+        bytes_sent: u64,
+        #[serde(rename = "bytesReceived")]
+        bytes_received: u64,
+        #[serde(rename = "sentPerSec")]
+        sent_per_sec: f64,
+        #[serde(rename = "receivedPerSec")]
+        received_per_sec: f64,
+        #[serde(rename = "requestSize")]
+        request_size: Option<u64>,
+        #[serde(rename = "responseHeaderSize")]
+        response_header_size: Option<u64>,
+        #[serde(rename = "responseSizePercentiles")]
+        response_size_percentiles: BTreeMap<String, u64>,
+    }
+
+    #[derive(Serialize)]
+    struct TimeSeriesLatency {
+        p50: f64,
+        p90: f64,
+        p99: f64,
+        max: f64,
+    }
+
+    #[derive(Serialize)]
+    struct TimeSeriesBucket {
+        start: f64,
+        requests: usize,
+        errors: usize,
+        bytes: u64,
+        #[serde(rename = "sentPerSec")]
+        sent_per_sec: f64,
+        #[serde(rename = "receivedPerSec")]
+        received_per_sec: f64,
+        latency: TimeSeriesLatency,
+    }
+
+    #[derive(Serialize)]
+    struct TimeSeries {
+        interval: f64,
+        buckets: Vec<TimeSeriesBucket>,
+    }
+
+    #[derive(Serialize)]
+    struct SloResult {
+        assertion: String,
+        actual: f64,
+        threshold: f64,
+        passed: bool,
     }
 
     #[derive(Serialize)]
@@ -196,6 +416,10 @@ fn print_json<W: Write>(
         response_time_histogram: BTreeMap<String, usize>,
         #[serde(rename = "latencyPercentiles")]
         latency_percentiles: BTreeMap<String, f64>,
+        #[serde(rename = "timeToFirstByte")]
+        time_to_first_byte: LatencyStatistics,
+        #[serde(rename = "downloadTime")]
+        download_time: LatencyStatistics,
         #[serde(
             rename = "responseTimeHistogramSuccessful",
             skip_serializing_if = "Option::is_none"
@@ -216,19 +440,50 @@ fn print_json<W: Write>(
             skip_serializing_if = "Option::is_none"
         )]
         latency_percentiles_not_successful: Option<BTreeMap<String, f64>>,
+        #[serde(rename = "statusCodeLatency", skip_serializing_if = "Option::is_none")]
+        status_code_latency: Option<BTreeMap<String, LatencyStatistics>>,
+        #[serde(rename = "errorTimeToFailure", skip_serializing_if = "Option::is_none")]
+        error_time_to_failure: Option<BTreeMap<String, LatencyStatistics>>,
+        #[serde(rename = "latencyCorrection", skip_serializing_if = "Option::is_none")]
+        latency_correction: Option<LatencyCorrection>,
         #[serde(rename = "rps")]
         rps: Rps,
+        throughput: Throughput,
+        #[serde(rename = "timeSeries", skip_serializing_if = "Option::is_none")]
+        time_series: Option<TimeSeries>,
         details: Details,
+        #[serde(rename = "tlsResumption")]
+        tls_resumption: TlsResumption,
+        connections: Connections,
+        retries: Retries,
+        redirects: Redirects,
+        #[serde(rename = "contentEncoding")]
+        content_encoding: BTreeMap<String, ContentEncoding>,
         #[serde(rename = "statusCodeDistribution")]
         status_code_distribution: BTreeMap<String, usize>,
         #[serde(rename = "errorDistribution")]
         error_distribution: BTreeMap<String, usize>,
+        #[serde(rename = "errorKinds")]
+        error_kinds: BTreeMap<String, ErrorKind>,
+        #[serde(rename = "headerDistribution")]
+        header_distribution: BTreeMap<String, BTreeMap<String, HeaderValue>>,
+        #[serde(rename = "urlDistribution")]
+        url_distribution: BTreeMap<String, UrlGroup>,
+        #[serde(rename = "remoteIpDistribution")]
+        remote_ip_distribution: BTreeMap<String, RemoteIp>,
+        #[serde(rename = "dnsChanges")]
+        dns_changes: Vec<f64>,
+        #[serde(rename = "dnsChangeCount")]
+        dns_change_count: usize,
+        #[serde(rename = "sloAssertions")]
+        slo_assertions: Vec<SloResult>,
     }
 
     let latency_stat = res.latency_stat();
 
     let summary = Summary {
         success_rate: res.success_rate(),
+        assertion_failures: res.assertion_failures(),
         total: total_duration.as_secs_f64(),
         slowest: latency_stat.max(),
         fastest: latency_stat.min(),
@@ -239,7 +494,7 @@ fn print_json<W: Write>(
         size_per_sec: res.total_data() as f64 / total_duration.as_secs_f64(),
     };
 
-    let durations_statistics = res.duration_all_statistics();
+    let durations_statistics = res.duration_all_statistics(stats);
 
     let response_time_histogram = durations_statistics
         .histogram
@@ -257,9 +512,11 @@ fn print_json<W: Write>(
     let mut latency_percentiles_successful: Option<BTreeMap<String, f64>> = None;
     let mut response_time_histogram_not_successful: Option<BTreeMap<String, usize>> = None;
     let mut latency_percentiles_not_successful: Option<BTreeMap<String, f64>> = None;
+    let mut status_code_latency: Option<BTreeMap<String, LatencyStatistics>> = None;
+    let mut error_time_to_failure: Option<BTreeMap<String, LatencyStatistics>> = None;
 
     if stats_success_breakdown {
-        let durations_successful_statistics = res.duration_successful_statistics();
+        let durations_successful_statistics = res.duration_successful_statistics(stats);
 
         response_time_histogram_successful = Some(
             durations_successful_statistics
@@ -277,7 +534,7 @@ fn print_json<W: Write>(
                 .collect(),
         );
 
-        let durations_not_successful_statistics = res.duration_not_successful_statistics();
+        let durations_not_successful_statistics = res.duration_not_successful_statistics(stats);
 
         response_time_histogram_not_successful = Some(
             durations_not_successful_statistics
@@ -294,41 +551,49 @@ fn print_json<W: Write>(
                 .map(|(p, v)| (format!("p{p}"), v))
                 .collect(),
         );
-    }
 
-    let mut ends = res
-        .end_times_from_start(start)
-        .map(|d| d.as_secs_f64())
-        .collect::<Vec<_>>();
-    ends.push(0.0);
-    float_ord::sort(&mut ends);
-
-    let mut rps: Vec<f64> = Vec::new();
-    // 10ms
-    const INTERVAL: f64 = 0.01;
-    let mut r = 0;
-    loop {
-        let prev_r = r;
-
-        // increment at least 1
-        if r + 1 < ends.len() {
-            r += 1;
-        }
+        status_code_latency = Some(
+            res.status_code_statistics(stats)
+                .into_iter()
+                .map(|(status, statistics)| (status.as_u16().to_string(), statistics.into()))
+                .collect(),
+        );
 
-        while r + 1 < ends.len() && ends[prev_r] + INTERVAL > ends[r + 1] {
-            r += 1;
-        }
+        error_time_to_failure = Some(
+            res.error_statistics(stats)
+                .into_iter()
+                .map(|(kind, statistics)| (kind.as_str().to_string(), statistics.into()))
+                .collect(),
+        );
+    }
 
-        if r == prev_r {
-            break;
-        }
+    let latency_correction = res
+        .latency_correction_stat(stats)
+        .map(|stat| LatencyCorrection {
+            service_time: stat.service_time.into(),
+            service_time_histogram: stat
+                .service_time_statistics
+                .histogram
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            service_time_percentiles: stat
+                .service_time_statistics
+                .percentiles
+                .into_iter()
+                .map(|(p, v)| (format!("p{p}"), v))
+                .collect(),
+            queueing_delay: stat.queueing_delay.into(),
+            queueing_delay_percentiles: stat
+                .queueing_delay_percentiles
+                .into_iter()
+                .map(|(p, v)| (format!("p{p}"), v))
+                .collect(),
+        });
 
-        let n = r - prev_r;
-        let t = ends[r] - ends[prev_r];
-        rps.push(n as f64 / t);
-    }
+    let mut rps = res.rps_samples(start);
 
-    let rps_percentiles = percentiles(&mut rps);
+    let rps_percentiles = percentiles(&mut rps, &stats.percentiles);
 
     let variance = rps.iter().collect::<Variance>();
     let rps = Rps {
@@ -339,22 +604,85 @@ fn print_json<W: Write>(
         percentiles: rps_percentiles,
     };
 
-    let status_code_distribution = res.status_code_distribution();
+    let throughput_stat = res.throughput_stat();
+    let connection_stat = res.connection_stat();
+    let throughput = Throughput {
+        bytes_sent: connection_stat.bytes_sent,
+        bytes_received: connection_stat.bytes_received,
+        sent_per_sec: connection_stat.bytes_sent as f64 / total_duration.as_secs_f64(),
+        received_per_sec: connection_stat.bytes_received as f64 / total_duration.as_secs_f64(),
+        request_size: throughput_stat.request_size(),
+        response_header_size: throughput_stat.response_header_size(),
+        response_size_percentiles: res
+            .response_size_percentiles(&stats.percentiles)
+            .into_iter()
+            .map(|(p, v)| (format!("p{p}"), v))
+            .collect(),
+    };
 
-    let dns_dialup_stat = res.dns_dialup_stat();
-    let dns_lookup_stat = res.dns_lookup_stat();
+    let time_series = res.time_series().map(|(interval, buckets)| TimeSeries {
+        interval: interval.as_secs_f64(),
+        buckets: buckets
+            .into_iter()
+            .map(|bucket| {
+                let percentile = |p: f64| {
+                    bucket
+                        .percentiles
+                        .iter()
+                        .find(|(q, _)| *q == p)
+                        .map_or(f64::NAN, |(_, v)| *v)
+                };
+                TimeSeriesBucket {
+                    start: bucket.offset.as_secs_f64(),
+                    requests: bucket.requests,
+                    errors: bucket.errors,
+                    bytes: bucket.bytes,
+                    sent_per_sec: bucket.bytes_sent as f64 / interval.as_secs_f64(),
+                    received_per_sec: bucket.bytes_received as f64 / interval.as_secs_f64(),
+                    latency: TimeSeriesLatency {
+                        p50: percentile(50.0),
+                        p90: percentile(90.0),
+                        p99: percentile(99.0),
+                        max: bucket.max,
+                    },
+                }
+            })
+            .collect(),
+    });
+
+    let status_code_distribution = res.status_code_distribution();
 
     let details = Details {
-        dns_dialup: Triple {
-            average: dns_dialup_stat.mean(),
-            fastest: dns_dialup_stat.min(),
-            slowest: dns_dialup_stat.max(),
-        },
-        dns_lookup: Triple {
-            average: dns_lookup_stat.mean(),
-            fastest: dns_lookup_stat.min(),
-            slowest: dns_lookup_stat.max(),
-        },
+        dns_dialup: res.dns_dialup_stat().into(),
+        dns_lookup: res.dns_lookup_stat().into(),
+        tcp_connect: res.tcp_connect_stat().into(),
+        tls_handshake: non_empty(res.tls_handshake_stat()).map(Into::into),
+        proxy_connect: non_empty(res.proxy_connect_stat()).map(Into::into),
+    };
+
+    let (resumed, connections) = res.tls_resumption();
+    let tls_resumption = TlsResumption {
+        resumed,
+        connections,
+    };
+
+    let connection_stat = res.connection_stat();
+    let connections = Connections {
+        connections: connection_stat.connections,
+        tls_connections: connection_stat.tls_connections,
+        reconnects: connection_stat.reconnects,
+        redirect_connections: connection_stat.redirect_connections,
+        requests_per_connection: connection_stat
+            .requests_per_connection
+            .iter()
+            .map(|(requests, count)| (requests.to_string(), *count))
+            .collect(),
+        requests_per_connection_percentiles: connection_stat
+            .requests_per_connection_percentiles(&stats.percentiles)
+            .into_iter()
+            .map(|(p, requests)| (format!("p{p}"), requests))
+            .collect(),
+        lifetime: connection_stat.lifetime.into(),
     };
 
     serde_json::to_writer_pretty(
@@ -363,17 +691,159 @@ fn print_json<W: Write>(
             summary,
             response_time_histogram,
             latency_percentiles,
+            time_to_first_byte: res.ttfb_statistics(stats).into(),
+            download_time: res.download_time_statistics(stats).into(),
             response_time_histogram_successful,
             latency_percentiles_successful,
             response_time_histogram_not_successful,
             latency_percentiles_not_successful,
+            status_code_latency,
+            error_time_to_failure,
+            latency_correction,
             rps,
+            throughput,
+            time_series,
             details,
+            tls_resumption,
+            connections,
+            retries: Retries {
+                first_attempt_success_rate: res.first_attempt_success_rate(),
+                distribution: res
+                    .retry_distribution()
+                    .iter()
+                    .map(|(retries, count)| (retries.to_string(), *count))
+                    .collect(),
+            },
+            redirects: Redirects {
+                hop_distribution: res
+                    .redirect_distribution()
+                    .into_iter()
+                    .map(|(hops, count)| (hops.to_string(), count))
+                    .collect(),
+                hops: res
+                    .redirect_hop_stats()
+                    .into_iter()
+                    .map(|hop| RedirectHop {
+                        status_code_distribution: hop
+                            .status_distribution
+                            .into_iter()
+                            .map(|(k, v)| (k.as_u16().to_string(), v))
+                            .collect(),
+                        latency: hop.latency.into(),
+                    })
+                    .collect(),
+            },
+            content_encoding: res
+                .content_encoding_distribution()
+                .into_iter()
+                .map(|(encoding, stat)| {
+                    (
+                        encoding.to_string(),
+                        ContentEncoding {
+                            count: stat.count,
+                            wire_bytes: stat.len_bytes,
+                            decoded_bytes: stat.decoded_len_bytes,
+                            ratio: stat.ratio(),
+                        },
+                    )
+                })
+                .collect(),
             status_code_distribution: status_code_distribution
                 .into_iter()
                 .map(|(k, v)| (k.as_u16().to_string(), v))
                 .collect(),
-            error_distribution: res.error_distribution().clone(),
+            error_distribution: res
+                .error_distribution()
+                .into_iter()
+                .map(|(kind, count)| (kind.as_str().to_string(), count))
+                .collect(),
+            error_kinds: res
+                .error_kinds()
+                .iter()
+                .map(|(kind, stat)| {
+                    (
+                        kind.as_str().to_string(),
+                        ErrorKind {
+                            count: stat.count,
+                            sample: stat.sample.clone(),
+                            first_seen: (stat.first_seen - start).as_secs_f64(),
+                            last_seen: (stat.last_seen - start).as_secs_f64(),
+                        },
+                    )
+                })
+                .collect(),
+            header_distribution: res
+                .header_value_distribution(&PERCENTILES)
+                .into_iter()
+                .map(|(name, values)| {
+                    (
+                        name,
+                        values
+                            .into_iter()
+                            .map(|(value, stat)| {
+                                (
+                                    header_value_label(value),
+                                    HeaderValue {
+                                        count: stat.count,
+                                        latency_percentiles: stat
+                                            .percentiles
+                                            .into_iter()
+                                            .map(|(p, v)| (format!("p{p}"), v))
+                                            .collect(),
+                                    },
+                                )
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+            url_distribution: res
+                .url_distribution()
+                .into_iter()
+                .map(|(url, stat)| {
+                    (
+                        url,
+                        UrlGroup {
+                            count: stat.count,
+                            error_rate: stat.error_rate,
+                            latency_percentiles: stat
+                                .percentiles
+                                .into_iter()
+                                .map(|(p, v)| (format!("p{p}"), v))
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+            remote_ip_distribution: res
+                .remote_ip_distribution()
+                .into_iter()
+                .map(|(ip, stat)| {
+                    (
+                        ip.to_string(),
+                        RemoteIp {
+                            count: stat.count,
+                            first_seen: (stat.first_seen - start).as_secs_f64(),
+                            last_seen: (stat.last_seen - start).as_secs_f64(),
+                        },
+                    )
+                })
+                .collect(),
+            dns_changes: connection_stat
+                .dns_change_times
+                .iter()
+                .map(|&t| (t - start).as_secs_f64())
+                .collect(),
+            dns_change_count: connection_stat.dns_changes,
+            slo_assertions: slo::evaluate(slo, res, total_duration)
+                .into_iter()
+                .map(|result| SloResult {
+                    assertion: result.assertion.text,
+                    actual: result.actual,
+                    threshold: result.assertion.threshold,
+                    passed: result.passed,
+                })
+                .collect(),
         },
     )
 }
@@ -382,7 +852,7 @@ fn print_csv<W: Write>(w: &mut W, start: Instant, res: &ResultData) -> std::io::
     // csv header
     writeln!(
         w,
-        "request-start,DNS,DNS+dialup,Response-delay,request-duration,bytes,status"
+        "request-start,DNS,DNS+dialup,Response-delay,request-duration,bytes,status,TCP-connect,TLS-handshake,Proxy-CONNECT"
     )?;
 
     let mut success_requests = res.success().to_vec();
@@ -396,13 +866,19 @@ fn print_csv<W: Write>(w: &mut W, start: Instant, res: &ResultData) -> std::io::
             ),
             None => (std::time::Duration::ZERO, std::time::Duration::ZERO),
         };
-        let first_byte = match request.first_byte {
-            Some(first_byte) => first_byte - request.start,
-            None => std::time::Duration::ZERO,
+        let first_byte = request.ttfb().unwrap_or_default();
+        // Each phase is a duration, not an offset from the request start
+        let (tcp_connect, tls_handshake, proxy_connect) = match request.connection_time {
+            Some(ct) => (
+                ct.tcp_connect_duration(),
+                ct.tls_handshake_duration().unwrap_or_default(),
+                ct.proxy_connect_duration().unwrap_or_default(),
+            ),
+            None => Default::default(),
         };
         writeln!(
             w,
-            "{},{},{},{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{}",
             (request.start - start).as_secs_f64(),
             dns_and_dialup.0.as_secs_f64(),
             dns_and_dialup.1.as_secs_f64(),
@@ -410,19 +886,26 @@ fn print_csv<W: Write>(w: &mut W, start: Instant, res: &ResultData) -> std::io::
             request.duration().as_secs_f64(),
             request.len_bytes,
             request.status.as_u16(),
+            tcp_connect.as_secs_f64(),
+            tls_handshake.as_secs_f64(),
+            proxy_connect.as_secs_f64(),
         )?;
     }
     Ok(())
 }
 
 /// Print all summary as Text
+#[allow(clippy::too_many_arguments)]
 fn print_summary<W: Write>(
     w: &mut W,
+    start: Instant,
     res: &ResultData,
     total_duration: Duration,
     disable_style: bool,
     stats_success_breakdown: bool,
     time_unit: Option<TimeScale>,
+    stats: &StatsConfig,
+    slo: &[SloAssertion],
 ) -> std::io::Result<()> {
     let style = StyleScheme {
         style_enabled: !disable_style,
@@ -437,6 +920,9 @@ fn print_summary<W: Write>(
             success_rate
         )
     )?;
+    if res.assertion_failures() > 0 {
+        writeln!(w, "  Assertion failures:\t{}", res.assertion_failures())?;
+    }
     let latency_stat = res.latency_stat();
     // Determine timescale automatically
     let timescale = if let Some(timescale) = time_unit {
@@ -501,7 +987,46 @@ fn print_summary<W: Write>(
     )?;
     writeln!(w)?;
 
-    let duration_all_statistics = res.duration_all_statistics();
+    let bytes = |n: u64| Byte::from_u64(n).get_appropriate_unit(byte_unit::UnitType::Binary);
+    let throughput_stat = res.throughput_stat();
+    let connection_stat = res.connection_stat();
+    writeln!(w, "{}", style.heading("Throughput:"))?;
+    for (label, n) in [
+        ("Sent", connection_stat.bytes_sent),
+        ("Received", connection_stat.bytes_received),
+    ] {
+        writeln!(
+            w,
+            "  {label}:\t{:.2} ({:.2}/sec)",
+            bytes(n),
+            bytes((n as f64 / total_duration.as_secs_f64()) as u64)
+        )?;
+    }
+    for (label, n) in [
+        ("Request size", throughput_stat.request_size()),
+        (
+            "Response header size",
+            throughput_stat.response_header_size(),
+        ),
+    ] {
+        if let Some(n) = n {
+            writeln!(w, "  {label}:\t{:.2}", bytes(n))?;
+        } else {
+            writeln!(w, "  {label}:\tNaN")?;
+        }
+    }
+    writeln!(w)?;
+
+    let response_size_percentiles = res.response_size_percentiles(&stats.percentiles);
+    if !response_size_percentiles.is_empty() {
+        writeln!(w, "{}", style.heading("Response size distribution:"))?;
+        for (p, n) in response_size_percentiles {
+            writeln!(w, "  {}% in {:.2}", percentile_label(p), bytes(n))?;
+        }
+        writeln!(w)?;
+    }
+
+    let duration_all_statistics = res.duration_all_statistics(stats);
 
     writeln!(w, "{}", style.heading("Response time histogram:"))?;
     print_histogram(w, &duration_all_statistics.histogram, style, timescale)?;
@@ -511,8 +1036,49 @@ fn print_summary<W: Write>(
     print_distribution(w, &duration_all_statistics.percentiles, style, timescale)?;
     writeln!(w)?;
 
+    // Responses without a body have neither
+    let ttfb_statistics = res.ttfb_statistics(stats);
+    if ttfb_statistics.histogram.iter().any(|&(_, n)| n > 0) {
+        let download_time_statistics = res.download_time_statistics(stats);
+        // Usually much shorter than the response time
+        let download_timescale = time_unit.unwrap_or_else(|| {
+            TimeScale::from_f64(
+                download_time_statistics
+                    .percentiles
+                    .last()
+                    .map_or(f64::NAN, |&(_, v)| v),
+            )
+        });
+
+        writeln!(w, "{}", style.heading("Time to first byte histogram:"))?;
+        print_histogram(w, &ttfb_statistics.histogram, style, timescale)?;
+        writeln!(w)?;
+
+        writeln!(w, "{}", style.heading("Time to first byte distribution:"))?;
+        print_distribution(w, &ttfb_statistics.percentiles, style, timescale)?;
+        writeln!(w)?;
+
+        writeln!(w, "{}", style.heading("Download time histogram:"))?;
+        print_histogram(
+            w,
+            &download_time_statistics.histogram,
+            style,
+            download_timescale,
+        )?;
+        writeln!(w)?;
+
+        writeln!(w, "{}", style.heading("Download time distribution:"))?;
+        print_distribution(
+            w,
+            &download_time_statistics.percentiles,
+            style,
+            download_timescale,
+        )?;
+        writeln!(w)?;
+    }
+
     if stats_success_breakdown {
-        let durations_successful_statics = res.duration_successful_statistics();
+        let durations_successful_statics = res.duration_successful_statistics(stats);
 
         writeln!(
             w,
@@ -535,7 +1101,7 @@ fn print_summary<W: Write>(
         )?;
         writeln!(w)?;
 
-        let durations_not_successful = res.duration_not_successful_statistics();
+        let durations_not_successful = res.duration_not_successful_statistics(stats);
 
         writeln!(
             w,
@@ -552,11 +1118,90 @@ fn print_summary<W: Write>(
         )?;
         print_distribution(w, &durations_not_successful.percentiles, style, timescale)?;
         writeln!(w)?;
+
+        for (status, statistics) in res.status_code_statistics(stats) {
+            writeln!(
+                w,
+                "{}",
+                style.heading(&format!(
+                    "Response time histogram ({} only):",
+                    status.as_str()
+                ))
+            )?;
+            print_histogram(w, &statistics.histogram, style, timescale)?;
+            writeln!(w)?;
+
+            writeln!(
+                w,
+                "{}",
+                style.heading(&format!(
+                    "Response time distribution ({} only):",
+                    status.as_str()
+                ))
+            )?;
+            print_distribution(w, &statistics.percentiles, style, timescale)?;
+            writeln!(w)?;
+        }
+
+        for (kind, statistics) in res.error_statistics(stats) {
+            let error = kind.as_str();
+            writeln!(
+                w,
+                "{}",
+                style.heading(&format!("Time to failure histogram ({error}):"))
+            )?;
+            print_histogram(w, &statistics.histogram, style, timescale)?;
+            writeln!(w)?;
+
+            writeln!(
+                w,
+                "{}",
+                style.heading(&format!("Time to failure distribution ({error}):"))
+            )?;
+            print_distribution(w, &statistics.percentiles, style, timescale)?;
+            writeln!(w)?;
+        }
     }
-    writeln!(w)?;
 
-    let dns_dialup_stat = res.dns_dialup_stat();
-    let dns_lookup_stat = res.dns_lookup_stat();
+    if let Some(stat) = res.latency_correction_stat(stats) {
+        writeln!(
+            w,
+            "{}",
+            style.heading("Latency correction (average, fastest, slowest):")
+        )?;
+        // Response time above is from when requests should have been sent
+        let mut print_stat = |label: &str, stat: &MinMaxMean| {
+            writeln!(
+                w,
+                "  {label}:\t{:.4} {timescale}, {:.4} {timescale}, {:.4} {timescale}",
+                stat.mean() / timescale.as_secs_f64(),
+                stat.min() / timescale.as_secs_f64(),
+                stat.max() / timescale.as_secs_f64()
+            )
+        };
+        print_stat("Response time", &latency_stat)?;
+        print_stat("Service time", &stat.service_time)?;
+        print_stat("Queueing delay", &stat.queueing_delay)?;
+        writeln!(w)?;
+
+        writeln!(w, "{}", style.heading("Service time histogram:"))?;
+        print_histogram(w, &stat.service_time_statistics.histogram, style, timescale)?;
+        writeln!(w)?;
+
+        writeln!(w, "{}", style.heading("Service time distribution:"))?;
+        print_distribution(
+            w,
+            &stat.service_time_statistics.percentiles,
+            style,
+            timescale,
+        )?;
+        writeln!(w)?;
+
+        writeln!(w, "{}", style.heading("Queueing delay distribution:"))?;
+        print_distribution(w, &stat.queueing_delay_percentiles, style, timescale)?;
+        writeln!(w)?;
+    }
+    writeln!(w)?;
 
     writeln!(
         w,
@@ -564,22 +1209,154 @@ fn print_summary<W: Write>(
         style.heading("Details (average, fastest, slowest):")
     )?;
 
-    writeln!(
-        w,
-        "  DNS+dialup:\t{:.4} {timescale}, {:.4} {timescale}, {:.4} {timescale}",
-        dns_dialup_stat.mean() / timescale.as_secs_f64(),
-        dns_dialup_stat.min() / timescale.as_secs_f64(),
-        dns_dialup_stat.max() / timescale.as_secs_f64()
-    )?;
-    writeln!(
-        w,
-        "  DNS-lookup:\t{:.4} {timescale}, {:.4} {timescale}, {:.4} {timescale}",
-        dns_lookup_stat.mean() / timescale.as_secs_f64(),
-        dns_lookup_stat.min() / timescale.as_secs_f64(),
-        dns_lookup_stat.max() / timescale.as_secs_f64()
-    )?;
+    let mut print_stat = |label: &str, stat: MinMaxMean| {
+        writeln!(
+            w,
+            "  {label}:\t{:.4} {timescale}, {:.4} {timescale}, {:.4} {timescale}",
+            stat.mean() / timescale.as_secs_f64(),
+            stat.min() / timescale.as_secs_f64(),
+            stat.max() / timescale.as_secs_f64()
+        )
+    };
+    print_stat("DNS+dialup", res.dns_dialup_stat())?;
+    print_stat("DNS-lookup", res.dns_lookup_stat())?;
+    print_stat("TCP-connect", res.tcp_connect_stat())?;
+    if let Some(stat) = non_empty(res.tls_handshake_stat()) {
+        print_stat("TLS-handshake", stat)?;
+    }
+    if let Some(stat) = non_empty(res.proxy_connect_stat()) {
+        print_stat("Proxy-CONNECT", stat)?;
+    }
     writeln!(w)?;
 
+    let (tls_resumed, tls_connections) = res.tls_resumption();
+    if tls_connections > 0 {
+        writeln!(w, "{}", style.heading("TLS session resumption:"))?;
+        writeln!(
+            w,
+            "  Resumed:	{tls_resumed} / {tls_connections} connections ({:.2}%)",
+            tls_resumed as f64 / tls_connections as f64 * 100.0
+        )?;
+        writeln!(w)?;
+    }
+
+    let connection_stat = res.connection_stat();
+    if connection_stat.connections > 0 {
+        writeln!(w, "{}", style.heading("Connections:"))?;
+        writeln!(
+            w,
+            "  Opened:\t{} ({} TLS)",
+            connection_stat.connections, connection_stat.tls_connections
+        )?;
+        writeln!(w, "  Reconnects:\t{}", connection_stat.reconnects)?;
+        if connection_stat.redirect_connections > 0 {
+            writeln!(
+                w,
+                "  For redirects:\t{}",
+                connection_stat.redirect_connections
+            )?;
+        }
+        // Empty if no request got a response
+        if let Some(lifetime) = non_empty(connection_stat.lifetime.clone()) {
+            let lifetime_timescale =
+                time_unit.unwrap_or_else(|| TimeScale::from_f64(lifetime.max()));
+            writeln!(
+                w,
+                "  Lifetime:\t{:.4} {lifetime_timescale}, {:.4} {lifetime_timescale}, {:.4} {lifetime_timescale} (average, shortest, longest)",
+                lifetime.mean() / lifetime_timescale.as_secs_f64(),
+                lifetime.min() / lifetime_timescale.as_secs_f64(),
+                lifetime.max() / lifetime_timescale.as_secs_f64()
+            )?;
+        }
+        writeln!(w)?;
+
+        writeln!(
+            w,
+            "{}",
+            style.heading("Requests per connection distribution:")
+        )?;
+        for (p, requests) in connection_stat.requests_per_connection_percentiles(&stats.percentiles)
+        {
+            writeln!(w, "  {}% in {requests} requests", percentile_label(p))?;
+        }
+        writeln!(w)?;
+    }
+
+    let retry_dist = res.retry_distribution();
+    // Only when any request was retried
+    if retry_dist.keys().any(|&retries| retries > 0) {
+        writeln!(w, "{}", style.heading("Retries:"))?;
+        writeln!(
+            w,
+            "  First attempt success rate:\t{:.2}%",
+            100.0 * res.first_attempt_success_rate()
+        )?;
+        writeln!(
+            w,
+            "  Final success rate:\t{:.2}%",
+            100.0 * res.success_rate()
+        )?;
+        for (retries, count) in retry_dist {
+            writeln!(w, "  [{count}] {retries} retries")?;
+        }
+        writeln!(w)?;
+    }
+
+    let redirect_hops = res.redirect_hop_stats();
+    if !redirect_hops.is_empty() {
+        writeln!(w, "{}", style.heading("Redirects:"))?;
+        for (hops, count) in res.redirect_distribution() {
+            writeln!(w, "  [{count}] {hops} hops")?;
+        }
+        writeln!(w, "  Hop latency (average, fastest, slowest):")?;
+        for (i, hop) in redirect_hops.iter().enumerate() {
+            let statuses = hop
+                .status_distribution
+                .iter()
+                .map(|(status, count)| format!("[{}] {count}", status.as_u16()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                w,
+                "    #{}:\t{:.4} {timescale}, {:.4} {timescale}, {:.4} {timescale} ({statuses})",
+                i + 1,
+                hop.latency.mean() / timescale.as_secs_f64(),
+                hop.latency.min() / timescale.as_secs_f64(),
+                hop.latency.max() / timescale.as_secs_f64()
+            )?;
+        }
+        writeln!(w)?;
+    }
+
+    let content_encoding_dist = res.content_encoding_distribution();
+    // Only with --decompress
+    if content_encoding_dist
+        .values()
+        .any(|stat| stat.decoded_len_bytes.is_some())
+    {
+        writeln!(
+            w,
+            "{}",
+            style.heading("Content-Encoding (wire size, decoded size, ratio):")
+        )?;
+        for (encoding, stat) in &content_encoding_dist {
+            let wire =
+                Byte::from_u64(stat.len_bytes).get_appropriate_unit(byte_unit::UnitType::Binary);
+            if let (Some(decoded), Some(ratio)) = (stat.decoded_len_bytes, stat.ratio()) {
+                let decoded =
+                    Byte::from_u64(decoded).get_appropriate_unit(byte_unit::UnitType::Binary);
+                writeln!(
+                    w,
+                    "  [{}] {encoding}:\t{wire:.2}, {decoded:.2}, {ratio:.2}x",
+                    stat.count
+                )?;
+            } else {
+                writeln!(w, "  [{}] {encoding}:\t{wire:.2}, -, -", stat.count)?;
+            }
+        }
+        writeln!(w)?;
+    }
+
     let status_dist: BTreeMap<http::StatusCode, usize> = res.status_code_distribution();
 
     let mut status_v: Vec<(http::StatusCode, usize)> = status_dist.into_iter().collect();
@@ -598,24 +1375,144 @@ fn print_summary<W: Write>(
         )?;
     }
 
-    let mut error_v: Vec<(String, usize)> = res
-        .error_distribution()
-        .iter()
-        .map(|(k, v)| (k.clone(), *v))
-        .collect();
-    error_v.sort_by_key(|t| std::cmp::Reverse(t.1));
+    let header_dist = res.header_value_distribution(&PERCENTILES);
+    if !header_dist.is_empty() {
+        writeln!(w)?;
+        writeln!(
+            w,
+            "{}",
+            style.heading("Header distribution (p50, p90, p99):")
+        )?;
+        for (name, values) in header_dist {
+            writeln!(w, "  {name}:")?;
+            let mut values: Vec<_> = values.into_iter().collect();
+            values.sort_by_key(|(_, stat)| std::cmp::Reverse(stat.count));
+            for (value, stat) in values {
+                let latency = stat
+                    .percentiles
+                    .iter()
+                    .filter(|(p, _)| [50.0, 90.0, 99.0].contains(p))
+                    .map(|(_, v)| format!("{:.4} {timescale}", v / timescale.as_secs_f64()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                writeln!(
+                    w,
+                    "    [{}] {}:\t{latency}",
+                    stat.count,
+                    header_value_label(value)
+                )?;
+            }
+        }
+    }
+
+    let url_dist = res.url_distribution();
+    if !url_dist.is_empty() {
+        writeln!(w)?;
+        writeln!(
+            w,
+            "{}",
+            style.heading("URL distribution (error rate, p50, p95, p99):")
+        )?;
+        let mut url_dist: Vec<_> = url_dist.into_iter().collect();
+        url_dist.sort_by_key(|(_, stat)| std::cmp::Reverse(stat.count));
+        for (url, stat) in url_dist {
+            let latency = stat
+                .percentiles
+                .iter()
+                .filter(|(p, _)| [50.0, 95.0, 99.0].contains(p))
+                .map(|(_, v)| format!("{:.4} {timescale}", v / timescale.as_secs_f64()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                w,
+                "  [{}] {url}:\t{:.2}%, {latency}",
+                stat.count,
+                stat.error_rate * 100.0
+            )?;
+        }
+    }
+
+    let remote_ip_dist = res.remote_ip_distribution();
+    let connection_stat = res.connection_stat();
+    // Only interesting when the traffic went to multiple addresses
+    if remote_ip_dist.len() > 1 {
+        writeln!(w)?;
+        writeln!(
+            w,
+            "{}",
+            style.heading("Remote IP distribution (first seen, last seen):")
+        )?;
+        for (ip, stat) in &remote_ip_dist {
+            writeln!(
+                w,
+                "  [{}] {ip} ({:.4} secs, {:.4} secs)",
+                stat.count,
+                (stat.first_seen - start).as_secs_f64(),
+                (stat.last_seen - start).as_secs_f64()
+            )?;
+        }
+    }
+    if connection_stat.dns_changes > 0 {
+        writeln!(w)?;
+        writeln!(w, "{}", style.heading("DNS changes:"))?;
+        for change in &connection_stat.dns_change_times {
+            writeln!(w, "  at {:.4} secs", (*change - start).as_secs_f64())?;
+        }
+        let more = connection_stat.dns_changes - connection_stat.dns_change_times.len();
+        if more > 0 {
+            writeln!(w, "  and {more} more")?;
+        }
+    }
+
+    let mut error_v = res.error_kinds().iter().collect::<Vec<_>>();
+    error_v.sort_by_key(|(_, stat)| std::cmp::Reverse(stat.count));
 
     if !error_v.is_empty() {
         writeln!(w)?;
         writeln!(w, "Error distribution:")?;
-        for (error, count) in error_v {
-            writeln!(w, "  [{count}] {error}")?;
+        for (kind, stat) in error_v {
+            writeln!(w, "  [{}] {}: {}", stat.count, kind.as_str(), stat.sample)?;
+        }
+    }
+
+    if !slo.is_empty() {
+        writeln!(w)?;
+        writeln!(w, "{}", style.heading("SLO assertions:"))?;
+        for result in slo::evaluate(slo, res, total_duration) {
+            writeln!(
+                w,
+                "{}",
+                style.slo(
+                    &format!(
+                        "  [{}] {}:\t{}",
+                        if result.passed { "PASS" } else { "FAIL" },
+                        result.assertion.text,
+                        result.assertion.metric.format(result.actual)
+                    ),
+                    result.passed
+                )
+            )?;
         }
     }
 
     Ok(())
 }
 
+/// Label for a value of a `--track-header` header
+pub fn header_value_label(value: TrackedValue) -> String {
+    match value {
+        TrackedValue::Value(value) => value,
+        TrackedValue::Missing => "(missing)".to_string(),
+        TrackedValue::Other => "(other)".to_string(),
+    }
+}
+
+/// None if no connection went through the phase
+fn non_empty(stat: MinMaxMean) -> Option<MinMaxMean> {
+    // The mean of an empty sample is NaN
+    (!stat.mean().is_nan()).then_some(stat)
+}
+
 /// This is used to print histogram of response time.
 fn print_histogram<W: Write>(
     w: &mut W,
@@ -664,15 +1561,26 @@ fn bar<W: Write>(w: &mut W, ratio: f64, style: StyleScheme, label: f64) -> std::
     Ok(())
 }
 
-fn percentile_iter(values: &mut [f64]) -> impl Iterator<Item = (f64, f64)> + '_ {
+fn percentile_iter<'a>(
+    values: &'a mut [f64],
+    percentiles: &'a [f64],
+) -> impl Iterator<Item = (f64, f64)> + 'a {
     float_ord::sort(values);
 
-    [10.0, 25.0, 50.0, 75.0, 90.0, 95.0, 99.0, 99.9, 99.99]
-        .iter()
-        .map(move |&p| {
-            let i = (p / 100.0 * values.len() as f64) as usize;
-            (p, *values.get(i).unwrap_or(&f64::NAN))
-        })
+    percentiles.iter().map(move |&p| {
+        let i = (p / 100.0 * values.len() as f64) as usize;
+        (p, *values.get(i).unwrap_or(&f64::NAN))
+    })
+}
+
+/// Two decimals unless the percentile has more, like 99.999
+fn percentile_label(p: f64) -> String {
+    let label = format!("{p:.2}");
+    if label.parse::<f64>() == Ok(p) {
+        label
+    } else {
+        p.to_string()
+    }
 }
 
 /// Print distribution of collection of f64
@@ -688,7 +1596,8 @@ fn print_distribution<W: Write>(
             "{}",
             style.latency_distribution(
                 &format!(
-                    "  {p:.2}% in {:.4} {timescale}",
+                    "  {}% in {:.4} {timescale}",
+                    percentile_label(*p),
                     v / timescale.as_secs_f64()
                 ),
                 *v
@@ -699,8 +1608,8 @@ fn print_distribution<W: Write>(
     Ok(())
 }
 
-fn percentiles(values: &mut [f64]) -> BTreeMap<String, f64> {
-    percentile_iter(values)
+fn percentiles(values: &mut [f64], percentiles: &[f64]) -> BTreeMap<String, f64> {
+    percentile_iter(values, percentiles)
         .map(|(p, v)| (format!("p{p}"), v))
         .collect()
 }
@@ -710,6 +1619,7 @@ mod tests {
     use float_cmp::assert_approx_eq;
 
     use super::*;
+    use crate::result_data::PERCENTILES;
 
     #[test]
     fn test_percentile_iter() {
@@ -718,7 +1628,7 @@ mod tests {
             11.0, 11.0, 11.0, 11.0, 11.0, 12.0, 12.0, 12.0, 12.0, 12.0, 12.0, 12.0, 12.0, 12.0,
             12.0, 15.0, 15.0, 15.0, 15.0, 15.0, 20.0, 20.0, 20.0, 25.0, 30.0,
         ];
-        let result: Vec<(f64, f64)> = percentile_iter(&mut values).collect();
+        let result: Vec<(f64, f64)> = percentile_iter(&mut values, &PERCENTILES).collect();
         assert_approx_eq!(&[f64], &[result[0].0, result[0].1], &[10.0, 5_f64]);
         assert_approx_eq!(&[f64], &[result[1].0, result[1].1], &[25.0, 11_f64]);
         assert_approx_eq!(&[f64], &[result[2].0, result[2].1], &[50.0, 12_f64]);
@@ -729,4 +1639,11 @@ mod tests {
         assert_approx_eq!(&[f64], &[result[7].0, result[7].1], &[99.9, 30_f64]);
         assert_approx_eq!(&[f64], &[result[8].0, result[8].1], &[99.99, 30_f64]);
     }
+
+    #[test]
+    fn test_percentile_label() {
+        assert_eq!(percentile_label(50.0), "50.00");
+        assert_eq!(percentile_label(99.9), "99.90");
+        assert_eq!(percentile_label(99.999), "99.999");
+    }
 }
@@ -1,26 +1,275 @@
 use std::{
     collections::BTreeMap,
+    net::IpAddr,
     time::{Duration, Instant},
 };
 
-use average::{Estimate, Max, Mean, Min, concatenate};
+use average::{Estimate, Max, Mean, Merge, Min};
+use hdrhistogram::Histogram;
 use hyper::StatusCode;
 
 use crate::{
-    client::{ClientError, RequestResult},
-    histogram::histogram,
+    client::{ClientError, ErrorKind, RequestResult},
+    connection_stats::ConnectionStat,
+    decompress::ContentEncoding,
+    histogram::{HistogramBins, histogram_of_counts},
 };
 
 /// Data container for the results of the all requests
-/// When a request is successful, the result is pushed to the `success` vector and the memory consumption will not be a problem because the number of successful requests is limited by network overhead.
-/// When a request fails, the error message is pushed to the `error` map because the number of error messages may huge.
+/// Results are aggregated when pushed, so the memory consumption doesn't grow with the number of requests.
+/// Latencies are kept in HDR histograms and the other stats are counters or running min/max/mean.
+/// Successful results themselves are kept only when `keep_results`, because CSV and SQLite outputs need each of them.
+/// When a request fails, the error message is counted in the `error` map because the number of error messages may huge.
 #[derive(Debug, Default)]
 pub struct ResultData {
-    success: Vec<RequestResult>,
-    error_distribution: BTreeMap<String, usize>,
+    success: Option<Vec<RequestResult>>,
+    success_count: usize,
+    first_attempt_success_count: usize,
+    error_kinds: BTreeMap<ErrorKind, ErrorKindStat>,
+    /// Responses which failed `--assert-*` checks. They are in `error_kinds` too.
+    assertion_failures: usize,
+    /// Number of requests by how many times they were retried, whether they succeeded or not
+    retry_distribution: BTreeMap<u32, usize>,
+    latency: MinMaxMean,
+    latency_successful: DurationHistogram,
+    latency_not_successful: DurationHistogram,
+    status_code_latency: BTreeMap<StatusCode, DurationHistogram>,
+    /// Time to failure of each error. Only of requests which failed after they were sent.
+    error_latency: BTreeMap<ErrorKind, DurationHistogram>,
+    /// Only of responses with a body
+    ttfb: DurationHistogram,
+    download_time: DurationHistogram,
+    // Only of requests with latency correction
+    service_time: MinMaxMean,
+    service_time_histogram: DurationHistogram,
+    queueing_delay: MinMaxMean,
+    queueing_delay_histogram: DurationHistogram,
+    status_code_distribution: BTreeMap<StatusCode, usize>,
+    dns_dialup: MinMaxMean,
+    dns_lookup: MinMaxMean,
+    tcp_connect: MinMaxMean,
+    proxy_connect: MinMaxMean,
+    tls_handshake: MinMaxMean,
+    total_data: u64,
+    /// Body sizes of the responses
+    response_size: SizeHistogram,
+    traffic: ThroughputStat,
+    ends: EndCounts,
+    remote_ip_distribution: BTreeMap<IpAddr, RemoteIpStat>,
+    content_encoding_distribution: BTreeMap<ContentEncoding, ContentEncodingStat>,
+    redirect_distribution: BTreeMap<usize, usize>,
+    redirect_hops: Vec<RedirectHopStat>,
+    header_value_distribution: BTreeMap<String, BTreeMap<TrackedValue, DurationHistogram>>,
+    /// Keyed by `RequestResult::url_group`
+    url_distribution: BTreeMap<String, UrlLatency>,
+    /// Taken from the `ConnectionRegistry` at the end of the run, because connections outlive the requests on them
+    connection_stat: ConnectionStat,
+    time_series: Option<TimeSeries>,
 }
 
-concatenate!(pub MinMaxMean, [Min, min], [Max, max], [Mean, mean]);
+/// What `ResultData` keeps besides the aggregated stats
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResultDataOptions {
+    /// Every successful result for `success()`
+    pub keep_results: bool,
+    /// Start of the run and the width of the buckets of `time_series()`
+    pub time_series: Option<(Instant, Duration)>,
+}
+
+/// Bytes of the successful requests
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThroughputStat {
+    pub requests: u64,
+    /// Requests in HTTP/1 form: the request lines, headers and bodies
+    pub request_bytes: u64,
+    /// Status lines and headers of the responses in HTTP/1 form
+    pub response_header_bytes: u64,
+}
+
+impl ThroughputStat {
+    fn merge(&mut self, other: &Self) {
+        self.requests += other.requests;
+        self.request_bytes += other.request_bytes;
+        self.response_header_bytes += other.response_header_bytes;
+    }
+
+    /// Average size of the requests in HTTP/1 form
+    pub fn request_size(&self) -> Option<u64> {
+        self.request_bytes.checked_div(self.requests)
+    }
+
+    /// Average size of the status lines and headers of the responses in HTTP/1 form
+    pub fn response_header_size(&self) -> Option<u64> {
+        self.response_header_bytes.checked_div(self.requests)
+    }
+}
+
+/// Same as `concatenate!(MinMaxMean, [Min, min], [Max, max], [Mean, mean])` but mergeable
+#[derive(Debug, Clone, Default)]
+pub struct MinMaxMean {
+    min: Min,
+    max: Max,
+    mean: Mean,
+}
+
+impl MinMaxMean {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, x: f64) {
+        self.min.add(x);
+        self.max.add(x);
+        self.mean.add(x);
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min.min()
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max.max()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean.mean()
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.min.merge(&other.min);
+        self.max.merge(&other.max);
+        self.mean.merge(&other.mean);
+    }
+}
+
+impl FromIterator<f64> for MinMaxMean {
+    fn from_iter<T: IntoIterator<Item = f64>>(iter: T) -> Self {
+        let mut stat = Self::new();
+        for x in iter {
+            stat.add(x);
+        }
+        stat
+    }
+}
+
+/// Requests sent to an IP address
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteIpStat {
+    pub count: usize,
+    /// Start of the first request
+    pub first_seen: Instant,
+    /// Start of the last request
+    pub last_seen: Instant,
+}
+
+/// Errors of an `ErrorKind`
+#[derive(Debug, Clone)]
+pub struct ErrorKindStat {
+    pub count: usize,
+    /// Message of the first error
+    pub sample: String,
+    /// When the first error happened
+    pub first_seen: Instant,
+    /// When the last error happened
+    pub last_seen: Instant,
+}
+
+/// Responses with a Content-Encoding
+#[derive(Debug, Clone, Copy)]
+pub struct ContentEncodingStat {
+    pub count: usize,
+    /// Body bytes as received
+    pub len_bytes: u64,
+    /// Body bytes after decoding. None if any of the responses wasn't decoded.
+    pub decoded_len_bytes: Option<u64>,
+}
+
+impl ContentEncodingStat {
+    /// Decoded size divided by wire size
+    pub fn ratio(&self) -> Option<f64> {
+        self.decoded_len_bytes
+            .filter(|_| self.len_bytes > 0)
+            .map(|decoded| decoded as f64 / self.len_bytes as f64)
+    }
+}
+
+/// The nth redirect of requests
+#[derive(Debug, Clone)]
+pub struct RedirectHopStat {
+    pub status_distribution: BTreeMap<StatusCode, usize>,
+    pub latency: MinMaxMean,
+}
+
+/// Distinct values of a `--track-header` header beyond this are counted as `TrackedValue::Other`
+const MAX_TRACKED_VALUES: usize = 100;
+
+/// A value of a `--track-header` header
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrackedValue {
+    Value(String),
+    /// The header is missing
+    Missing,
+    /// Values after the first `MAX_TRACKED_VALUES` ones
+    Other,
+}
+
+impl TrackedValue {
+    /// Keeps `self` unless `values` already has too many other values
+    fn capped<V>(self, values: &BTreeMap<TrackedValue, V>) -> Self {
+        if let Self::Value(_) = self {
+            if !values.contains_key(&self)
+                && values
+                    .keys()
+                    .filter(|v| matches!(v, Self::Value(_)))
+                    .count()
+                    >= MAX_TRACKED_VALUES
+            {
+                return Self::Other;
+            }
+        }
+        self
+    }
+}
+
+/// Responses with a value of a `--track-header` header
+pub struct HeaderValueStat {
+    pub count: usize,
+    /// Latency percentiles of the responses
+    pub percentiles: Vec<(f64, f64)>,
+}
+
+/// URL groups beyond this are counted as `OTHER_URL_GROUP`
+const MAX_URL_GROUPS: usize = 100;
+
+/// Key of the URL groups after the first `MAX_URL_GROUPS` ones
+pub const OTHER_URL_GROUP: &str = "(other)";
+
+/// Latency of the responses of a URL group
+#[derive(Debug, Default)]
+struct UrlLatency {
+    latency: DurationHistogram,
+    not_successful: usize,
+}
+
+/// Responses of a URL or a `--url-group` pattern
+pub struct UrlStat {
+    pub count: usize,
+    /// Rate of the responses whose status code isn't successful.
+    /// Requests which failed without a response can't be attributed to a URL.
+    pub error_rate: f64,
+    /// Latency percentiles of the responses
+    pub percentiles: Vec<(f64, f64)>,
+}
+
+/// Requests with latency correction. Their latency is from when they should have been sent.
+pub struct LatencyCorrectionStat {
+    /// From when requests were actually sent
+    pub service_time: MinMaxMean,
+    pub service_time_statistics: Statistics,
+    /// From when requests should have been sent to when they were sent
+    pub queueing_delay: MinMaxMean,
+    pub queueing_delay_percentiles: Vec<(f64, f64)>,
+}
 
 pub struct Statistics {
     pub percentiles: Vec<(f64, f64)>,
@@ -29,161 +278,997 @@ pub struct Statistics {
 
 impl Statistics {
     /* private */
-    fn new(data: &mut [f64]) -> Self {
-        float_ord::sort(data);
+    fn new(data: &DurationHistogram, config: &StatsConfig) -> Self {
+        Self {
+            percentiles: data.percentiles(&config.percentiles),
+            histogram: data.histogram(&config.histogram_bins),
+        }
+    }
+}
 
+/// Percentiles used by default and by the compact per-header and per-URL stats
+pub const PERCENTILES: [f64; 9] = [10.0, 25.0, 50.0, 75.0, 90.0, 95.0, 99.0, 99.9, 99.99];
+
+/// Which percentiles and histogram buckets `Statistics` have
+#[derive(Debug, Clone)]
+pub struct StatsConfig {
+    pub percentiles: Vec<f64>,
+    pub histogram_bins: HistogramBins,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
         Self {
-            percentiles: percentile_iter(data).collect(),
-            histogram: histogram(data, 11),
+            percentiles: PERCENTILES.to_vec(),
+            histogram_bins: HistogramBins::default(),
         }
     }
 }
 
-fn percentile_iter(values: &mut [f64]) -> impl Iterator<Item = (f64, f64)> + '_ {
-    float_ord::sort(values);
+/// Durations in a HDR histogram of nanoseconds with 3 significant digits
+#[derive(Debug, Clone, Default)]
+pub struct DurationHistogram {
+    /// Allocated by the first record because most of them are empty
+    histogram: Option<Histogram<u64>>,
+    /// Exact ones because the histogram only knows the range of the bucket
+    min: u64,
+    max: u64,
+}
 
-    [10.0, 25.0, 50.0, 75.0, 90.0, 95.0, 99.0, 99.9, 99.99]
-        .iter()
-        .map(move |&p| {
-            let i = (p / 100.0 * values.len() as f64) as usize;
-            (p, *values.get(i).unwrap_or(&f64::NAN))
-        })
+impl DurationHistogram {
+    pub fn record(&mut self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        if self.is_empty() {
+            self.min = nanos;
+            self.max = nanos;
+        } else {
+            self.min = self.min.min(nanos);
+            self.max = self.max.max(nanos);
+        }
+        // Auto-resizing
+        let histogram = self
+            .histogram
+            .get_or_insert_with(|| Histogram::new(3).unwrap());
+        // `saturating_record` doesn't resize
+        if histogram.record(nanos).is_err() {
+            histogram.saturating_record(nanos);
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        let Some(other_histogram) = other.histogram.as_ref().filter(|h| !h.is_empty()) else {
+            return;
+        };
+        if self.is_empty() {
+            self.min = other.min;
+            self.max = other.max;
+        } else {
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        self.histogram
+            .get_or_insert_with(|| Histogram::new(3).unwrap())
+            .add(other_histogram)
+            .unwrap();
+    }
+
+    pub fn len(&self) -> usize {
+        self.histogram.as_ref().map_or(0, |h| h.len() as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A representative value of the bucket in seconds. The buckets of the min and max are represented by them.
+    fn value_secs(&self, histogram: &Histogram<u64>, value: u64) -> f64 {
+        let value = if histogram.lowest_equivalent(value) <= self.min {
+            self.min
+        } else if histogram.highest_equivalent(value) >= self.max {
+            self.max
+        } else {
+            histogram.median_equivalent(value)
+        };
+        Duration::from_nanos(value).as_secs_f64()
+    }
+
+    /// Returns (percentile, seconds). Seconds are NaN if empty.
+    pub fn percentiles(&self, percentiles: &[f64]) -> Vec<(f64, f64)> {
+        percentiles
+            .iter()
+            .map(|&p| {
+                let value = match &self.histogram {
+                    Some(histogram) if !histogram.is_empty() => {
+                        self.value_secs(histogram, histogram.value_at_quantile(p / 100.0))
+                    }
+                    _ => f64::NAN,
+                };
+                (p, value)
+            })
+            .collect()
+    }
+
+    /// Returns buckets between the min and max in seconds
+    pub fn histogram(&self, bins: &HistogramBins) -> Vec<(f64, usize)> {
+        match &self.histogram {
+            Some(histogram) if !histogram.is_empty() => histogram_of_counts(
+                histogram.iter_recorded().map(|v| {
+                    (
+                        self.value_secs(histogram, v.value_iterated_to()),
+                        v.count_at_value() as usize,
+                    )
+                }),
+                Duration::from_nanos(self.min).as_secs_f64(),
+                Duration::from_nanos(self.max).as_secs_f64(),
+                bins,
+            ),
+            // Same as `histogram::histogram` of no values
+            _ => histogram_of_counts([], f64::INFINITY, f64::NEG_INFINITY, bins),
+        }
+    }
+}
+
+/// Sizes in a HDR histogram of bytes with 3 significant digits
+#[derive(Debug, Clone, Default)]
+struct SizeHistogram {
+    /// Allocated by the first record because most of them are empty
+    histogram: Option<Histogram<u64>>,
+    /// Exact one because the histogram only knows the range of the bucket
+    max: u64,
+}
+
+impl SizeHistogram {
+    fn record(&mut self, bytes: u64) {
+        self.max = self.max.max(bytes);
+        // Auto-resizing
+        let histogram = self
+            .histogram
+            .get_or_insert_with(|| Histogram::new(3).unwrap());
+        if histogram.record(bytes).is_err() {
+            histogram.saturating_record(bytes);
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        let Some(other_histogram) = &other.histogram else {
+            return;
+        };
+        self.max = self.max.max(other.max);
+        self.histogram
+            .get_or_insert_with(|| Histogram::new(3).unwrap())
+            .add(other_histogram)
+            .unwrap();
+    }
+
+    /// Returns (percentile, bytes). Empty if there's no value.
+    fn percentiles(&self, percentiles: &[f64]) -> Vec<(f64, u64)> {
+        let Some(histogram) = self.histogram.as_ref().filter(|h| !h.is_empty()) else {
+            return Vec::new();
+        };
+        percentiles
+            .iter()
+            .map(|&p| (p, histogram.value_at_quantile(p / 100.0).min(self.max)))
+            .collect()
+    }
+}
+
+/// Requests which ended in each `interval` since `start`. It grows with the duration of the run, not the number of requests.
+#[derive(Debug, Clone)]
+struct TimeSeries {
+    start: Instant,
+    interval: Duration,
+    buckets: Vec<SeriesBucket>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SeriesBucket {
+    requests: usize,
+    errors: usize,
+    bytes: u64,
+    /// From `ConnectionStat::bytes_series`
+    bytes_sent: u64,
+    bytes_received: u64,
+    latency: SparseHistogram,
+}
+
+impl TimeSeries {
+    fn bucket(&mut self, end: Instant) -> &mut SeriesBucket {
+        let i = (end.saturating_duration_since(self.start).as_nanos() / self.interval.as_nanos())
+            as usize;
+        if self.buckets.len() <= i {
+            self.buckets.resize_with(i + 1, Default::default);
+        }
+        &mut self.buckets[i]
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if self.buckets.len() < other.buckets.len() {
+            self.buckets
+                .resize_with(other.buckets.len(), Default::default);
+        }
+        for (bucket, other) in self.buckets.iter_mut().zip(&other.buckets) {
+            bucket.requests += other.requests;
+            bucket.errors += other.errors;
+            bucket.bytes += other.bytes;
+            bucket.bytes_sent += other.bytes_sent;
+            bucket.bytes_received += other.bytes_received;
+            bucket.latency.merge(&other.latency);
+        }
+    }
+
+    fn set_socket_bytes(&mut self, bytes_series: &[(u64, u64)]) {
+        if self.buckets.len() < bytes_series.len() {
+            self.buckets
+                .resize_with(bytes_series.len(), Default::default);
+        }
+        for (i, bucket) in self.buckets.iter_mut().enumerate() {
+            (bucket.bytes_sent, bucket.bytes_received) =
+                bytes_series.get(i).copied().unwrap_or_default();
+        }
+    }
+}
+
+/// Durations in log-scale buckets 1% wide, of which only ones with values are allocated.
+/// It's less precise than `DurationHistogram` but small enough to have one for each bucket of a time series.
+#[derive(Debug, Clone, Default)]
+struct SparseHistogram {
+    counts: BTreeMap<i32, usize>,
+    len: usize,
+    max: Duration,
+}
+
+impl SparseHistogram {
+    const BASE: f64 = 1.01;
+
+    fn record(&mut self, duration: Duration) {
+        let nanos = duration.as_nanos().max(1) as f64;
+        let bucket = (nanos.ln() / Self::BASE.ln()).floor() as i32;
+        *self.counts.entry(bucket).or_insert(0) += 1;
+        self.len += 1;
+        self.max = self.max.max(duration);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (&bucket, &n) in &other.counts {
+            *self.counts.entry(bucket).or_insert(0) += n;
+        }
+        self.len += other.len;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Returns (percentile, seconds). Seconds are NaN if empty.
+    fn percentiles(&self, percentiles: &[f64]) -> Vec<(f64, f64)> {
+        percentiles
+            .iter()
+            .map(|&p| {
+                let rank = ((p / 100.0 * self.len as f64).ceil() as usize).max(1);
+                let mut seen = 0;
+                let value = self
+                    .counts
+                    .iter()
+                    .find(|&(_, &n)| {
+                        seen += n;
+                        seen >= rank
+                    })
+                    // The middle of the bucket but not beyond the max
+                    .map_or(f64::NAN, |(&bucket, _)| {
+                        (Self::BASE.powf(bucket as f64 + 0.5) / 1e9).min(self.max.as_secs_f64())
+                    });
+                (p, value)
+            })
+            .collect()
+    }
+}
+
+/// Requests which ended in a bucket of the time series
+#[derive(Debug, Clone)]
+pub struct TimeSeriesBucket {
+    /// From the start of the run to the start of the bucket
+    pub offset: Duration,
+    /// Including failed ones
+    pub requests: usize,
+    pub errors: usize,
+    pub bytes: u64,
+    /// Written to the sockets of the connections, as `ConnectionStat::bytes_series`
+    pub bytes_sent: u64,
+    /// Read from the sockets of the connections, as `ConnectionStat::bytes_series`
+    pub bytes_received: u64,
+    /// (percentile, seconds) of the responses. Seconds are NaN if there's no response.
+    pub percentiles: Vec<(f64, f64)>,
+    /// Seconds of the slowest response. NaN if there's no response.
+    pub max: f64,
+}
+
+/// Percentiles in each bucket of a time series
+const TIME_SERIES_PERCENTILES: [f64; 3] = [50.0, 90.0, 99.0];
+
+/// Number of responses which ended in each 10ms since `base`, to calculate requests per second without keeping every end time.
+/// It grows with the duration of the run, not the number of requests.
+#[derive(Debug, Default)]
+struct EndCounts {
+    base: Option<Instant>,
+    counts: Vec<u32>,
+}
+
+impl EndCounts {
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    fn add(&mut self, end: Instant, n: u32) {
+        let base = *self.base.get_or_insert(end);
+        let base = if end < base {
+            let shift = (base - end).as_nanos().div_ceil(Self::INTERVAL.as_nanos()) as u32;
+            self.counts
+                .splice(0..0, std::iter::repeat_n(0, shift as usize));
+            let base = base - Self::INTERVAL * shift;
+            self.base = Some(base);
+            base
+        } else {
+            base
+        };
+        let i = ((end - base).as_nanos() / Self::INTERVAL.as_nanos()) as usize;
+        if self.counts.len() <= i {
+            self.counts.resize(i + 1, 0);
+        }
+        self.counts[i] += n;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        let Some(base) = other.base else {
+            return;
+        };
+        for (i, &n) in other.counts.iter().enumerate() {
+            if n > 0 {
+                self.add(base + Self::INTERVAL * i as u32, n);
+            }
+        }
+    }
 }
 
 impl ResultData {
+    pub fn new(options: ResultDataOptions) -> Self {
+        Self {
+            success: options.keep_results.then(Vec::new),
+            time_series: options.time_series.map(|(start, interval)| TimeSeries {
+                start,
+                interval,
+                buckets: Vec::new(),
+            }),
+            ..Default::default()
+        }
+    }
+
     #[inline]
     pub fn push(&mut self, result: Result<RequestResult, ClientError>) {
+        self.record(&result);
+        if let (Ok(result), Some(success)) = (result, &mut self.success) {
+            success.push(result);
+        }
+    }
+
+    /// Same as `push` but never keeps the result
+    pub fn record(&mut self, result: &Result<RequestResult, ClientError>) {
+        let retries = match result {
+            Ok(result) => result.retries,
+            Err(ClientError::Failed { retries, .. }) => *retries,
+            Err(_) => 0,
+        };
+        *self.retry_distribution.entry(retries).or_insert(0) += 1;
         match result {
-            Ok(result) => self.success.push(result),
+            Ok(result) => self.aggregate(result),
             Err(err) => {
-                let count = self.error_distribution.entry(err.to_string()).or_insert(0);
-                *count += 1;
+                let (err, duration, end) = match err {
+                    ClientError::Failed {
+                        error,
+                        duration,
+                        end,
+                        ..
+                    } => (error.as_ref(), *duration, *end),
+                    // Not from the workers
+                    err => (err, None, Instant::now()),
+                };
+                if let ClientError::AssertionFailed { .. } = err {
+                    self.assertion_failures += 1;
+                }
+                let message = err.to_string();
+                if let Some(time_series) = &mut self.time_series {
+                    let bucket = time_series.bucket(end);
+                    bucket.requests += 1;
+                    bucket.errors += 1;
+                }
+                let kind = err.kind();
+                if let Some(duration) = duration {
+                    self.error_latency.entry(kind).or_default().record(duration);
+                }
+                merge_error_kind_stat(
+                    &mut self.error_kinds,
+                    kind,
+                    ErrorKindStat {
+                        count: 1,
+                        sample: message,
+                        first_seen: end,
+                        last_seen: end,
+                    },
+                );
+            }
+        }
+    }
+
+    fn aggregate(&mut self, result: &RequestResult) {
+        self.success_count += 1;
+        if result.retries == 0 {
+            self.first_attempt_success_count += 1;
+        }
+
+        let duration = result.duration();
+        self.latency.add(duration.as_secs_f64());
+        if result.status.is_success() {
+            self.latency_successful.record(duration);
+        } else {
+            self.latency_not_successful.record(duration);
+        }
+        self.status_code_latency
+            .entry(result.status)
+            .or_default()
+            .record(duration);
+        if let (Some(ttfb), Some(download_time)) = (result.ttfb(), result.download_time()) {
+            self.ttfb.record(ttfb);
+            self.download_time.record(download_time);
+        }
+        if let Some(queueing_delay) = result.queueing_delay() {
+            let service_time = result.service_time();
+            self.service_time.add(service_time.as_secs_f64());
+            self.service_time_histogram.record(service_time);
+            self.queueing_delay.add(queueing_delay.as_secs_f64());
+            self.queueing_delay_histogram.record(queueing_delay);
+        }
+        *self
+            .status_code_distribution
+            .entry(result.status)
+            .or_insert(0) += 1;
+        self.total_data += result.len_bytes as u64;
+        self.response_size.record(result.len_bytes as u64);
+        self.traffic.merge(&ThroughputStat {
+            requests: 1,
+            request_bytes: result.request_bytes,
+            response_header_bytes: result.response_header_bytes,
+        });
+        self.ends.add(result.end, 1);
+        if let Some(time_series) = &mut self.time_series {
+            let bucket = time_series.bucket(result.end);
+            bucket.requests += 1;
+            bucket.bytes += result.len_bytes as u64;
+            bucket.latency.record(duration);
+        }
+
+        if let Some(ct) = result.connection_time {
+            self.dns_dialup
+                .add((ct.dialup - result.start).as_secs_f64());
+            self.dns_lookup
+                .add((ct.dns_lookup - result.start).as_secs_f64());
+            self.tcp_connect
+                .add(ct.tcp_connect_duration().as_secs_f64());
+            if let Some(d) = ct.proxy_connect_duration() {
+                self.proxy_connect.add(d.as_secs_f64());
+            }
+            if let Some(d) = ct.tls_handshake_duration() {
+                self.tls_handshake.add(d.as_secs_f64());
+            }
+        }
+
+        if let Some(remote_ip) = result.remote_ip {
+            merge_remote_ip_stat(
+                &mut self.remote_ip_distribution,
+                remote_ip,
+                RemoteIpStat {
+                    count: 1,
+                    first_seen: result.start,
+                    last_seen: result.start,
+                },
+            );
+        }
+
+        merge_content_encoding_stat(
+            &mut self.content_encoding_distribution,
+            result.content_encoding,
+            ContentEncodingStat {
+                count: 1,
+                len_bytes: result.len_bytes as u64,
+                decoded_len_bytes: result.decoded_len_bytes.map(|n| n as u64),
+            },
+        );
+
+        *self
+            .redirect_distribution
+            .entry(result.redirects.len())
+            .or_insert(0) += 1;
+        for (i, hop) in result.redirects.iter().enumerate() {
+            if self.redirect_hops.len() <= i {
+                self.redirect_hops.push(RedirectHopStat {
+                    status_distribution: BTreeMap::new(),
+                    latency: MinMaxMean::new(),
+                });
+            }
+            *self.redirect_hops[i]
+                .status_distribution
+                .entry(hop.status)
+                .or_insert(0) += 1;
+            self.redirect_hops[i]
+                .latency
+                .add(hop.duration.as_secs_f64());
+        }
+
+        for (name, value) in &result.tracked_headers {
+            let value = value.as_ref().map_or(TrackedValue::Missing, |v| {
+                TrackedValue::Value(String::from_utf8_lossy(v.as_bytes()).into_owned())
+            });
+            let values = self
+                .header_value_distribution
+                .entry(name.as_str().to_string())
+                .or_default();
+            values
+                .entry(value.capped(values))
+                .or_default()
+                .record(duration);
+        }
+
+        if let Some(url_group) = &result.url_group {
+            let stat = url_group_stat(&mut self.url_distribution, url_group);
+            stat.latency.record(duration);
+            if !result.status.is_success() {
+                stat.not_successful += 1;
             }
         }
     }
 
     pub fn len(&self) -> usize {
-        self.success.len() + self.error_distribution.values().sum::<usize>()
+        self.success_count
+            + self
+                .error_kinds
+                .values()
+                .map(|stat| stat.count)
+                .sum::<usize>()
     }
 
     pub fn merge(&mut self, other: ResultData) {
-        self.success.extend(other.success);
-        for (k, v) in other.error_distribution {
-            let count = self.error_distribution.entry(k).or_insert(0);
-            *count += v;
+        if let (Some(success), Some(other_success)) = (&mut self.success, other.success) {
+            success.extend(other_success);
+        }
+        self.success_count += other.success_count;
+        self.first_attempt_success_count += other.first_attempt_success_count;
+        self.assertion_failures += other.assertion_failures;
+        for (k, v) in other.retry_distribution {
+            *self.retry_distribution.entry(k).or_insert(0) += v;
+        }
+        for (kind, stat) in other.error_kinds {
+            merge_error_kind_stat(&mut self.error_kinds, kind, stat);
+        }
+        match (&mut self.time_series, other.time_series) {
+            (Some(time_series), Some(other)) => time_series.merge(&other),
+            (None, other) => self.time_series = other,
+            _ => {}
+        }
+
+        self.latency.merge(&other.latency);
+        self.latency_successful.merge(&other.latency_successful);
+        self.latency_not_successful
+            .merge(&other.latency_not_successful);
+        for (status, histogram) in other.status_code_latency {
+            self.status_code_latency
+                .entry(status)
+                .or_default()
+                .merge(&histogram);
+        }
+        for (kind, histogram) in other.error_latency {
+            self.error_latency
+                .entry(kind)
+                .or_default()
+                .merge(&histogram);
+        }
+        self.service_time.merge(&other.service_time);
+        self.ttfb.merge(&other.ttfb);
+        self.download_time.merge(&other.download_time);
+        self.service_time_histogram
+            .merge(&other.service_time_histogram);
+        self.queueing_delay.merge(&other.queueing_delay);
+        self.queueing_delay_histogram
+            .merge(&other.queueing_delay_histogram);
+        for (k, v) in other.status_code_distribution {
+            *self.status_code_distribution.entry(k).or_insert(0) += v;
+        }
+        self.dns_dialup.merge(&other.dns_dialup);
+        self.dns_lookup.merge(&other.dns_lookup);
+        self.tcp_connect.merge(&other.tcp_connect);
+        self.proxy_connect.merge(&other.proxy_connect);
+        self.tls_handshake.merge(&other.tls_handshake);
+        self.total_data += other.total_data;
+        self.response_size.merge(&other.response_size);
+        self.traffic.merge(&other.traffic);
+        self.ends.merge(&other.ends);
+
+        for (k, v) in other.remote_ip_distribution {
+            merge_remote_ip_stat(&mut self.remote_ip_distribution, k, v);
+        }
+        for (k, v) in other.content_encoding_distribution {
+            merge_content_encoding_stat(&mut self.content_encoding_distribution, k, v);
+        }
+        for (k, v) in other.redirect_distribution {
+            *self.redirect_distribution.entry(k).or_insert(0) += v;
+        }
+        for (i, hop) in other.redirect_hops.into_iter().enumerate() {
+            match self.redirect_hops.get_mut(i) {
+                Some(stat) => {
+                    for (k, v) in hop.status_distribution {
+                        *stat.status_distribution.entry(k).or_insert(0) += v;
+                    }
+                    stat.latency.merge(&hop.latency);
+                }
+                None => self.redirect_hops.push(hop),
+            }
+        }
+        for (name, values) in other.header_value_distribution {
+            let dist = self.header_value_distribution.entry(name).or_default();
+            for (value, histogram) in values {
+                dist.entry(value.capped(dist))
+                    .or_default()
+                    .merge(&histogram);
+            }
+        }
+        for (url_group, other_stat) in other.url_distribution {
+            let stat = url_group_stat(&mut self.url_distribution, &url_group);
+            stat.latency.merge(&other_stat.latency);
+            stat.not_successful += other_stat.not_successful;
         }
     }
 
-    // An existence of this method doesn't prevent us to using hdrhistogram.
-    // Because this is only called from `monitor` and `monitor` can collect own data.
+    /// Empty unless created with `keep_results`.
+    /// `monitor` collects own data instead of this.
     pub fn success(&self) -> &[RequestResult] {
-        &self.success
+        self.success.as_deref().unwrap_or_default()
     }
 
-    // It's very happy if you can provide all below methods without array (= non liner memory consumption) and fast `push` runtime.
-
     pub fn success_rate(&self) -> f64 {
-        let dead_line = ClientError::Deadline.to_string();
+        let numerator = self.success_count;
+
+        numerator as f64 / self.success_rate_denominator() as f64
+    }
+
+    /// Success rate as if requests weren't retried
+    pub fn first_attempt_success_rate(&self) -> f64 {
+        let numerator = self.first_attempt_success_count;
+
+        numerator as f64 / self.success_rate_denominator() as f64
+    }
+
+    fn success_rate_denominator(&self) -> usize {
         // We ignore deadline errors which are because of `-z` option, not because of the server
-        let denominator = self.success.len()
+        self.success_count
             + self
-                .error_distribution
+                .error_kinds
                 .iter()
-                .filter_map(|(k, v)| if k == &dead_line { None } else { Some(v) })
-                .sum::<usize>();
-        let numerator = self.success.len();
-
-        numerator as f64 / denominator as f64
+                .filter(|(kind, _)| **kind != ErrorKind::Deadline)
+                .map(|(_, stat)| stat.count)
+                .sum::<usize>()
     }
 
     pub fn latency_stat(&self) -> MinMaxMean {
-        self.success
+        self.latency.clone()
+    }
+
+    /// None unless any request used latency correction
+    pub fn latency_correction_stat(&self, config: &StatsConfig) -> Option<LatencyCorrectionStat> {
+        if self.service_time_histogram.is_empty() {
+            return None;
+        }
+        Some(LatencyCorrectionStat {
+            service_time: self.service_time.clone(),
+            service_time_statistics: Statistics::new(&self.service_time_histogram, config),
+            queueing_delay: self.queueing_delay.clone(),
+            queueing_delay_percentiles: self
+                .queueing_delay_histogram
+                .percentiles(&config.percentiles),
+        })
+    }
+
+    /// Returns the number of errors of each kind
+    pub fn error_distribution(&self) -> BTreeMap<ErrorKind, usize> {
+        self.error_kinds
             .iter()
-            .map(|result| result.duration().as_secs_f64())
+            .map(|(kind, stat)| (*kind, stat.count))
             .collect()
     }
 
-    pub fn error_distribution(&self) -> &BTreeMap<String, usize> {
-        &self.error_distribution
+    pub fn error_kinds(&self) -> &BTreeMap<ErrorKind, ErrorKindStat> {
+        &self.error_kinds
     }
 
-    pub fn end_times_from_start(&self, start: Instant) -> impl Iterator<Item = Duration> + '_ {
-        self.success.iter().map(move |result| result.end - start)
+    pub fn assertion_failures(&self) -> usize {
+        self.assertion_failures
     }
 
-    pub fn status_code_distribution(&self) -> BTreeMap<StatusCode, usize> {
-        let mut dist = BTreeMap::new();
-        for result in &self.success {
-            let count = dist.entry(result.status).or_insert(0);
-            *count += 1;
+    pub fn retry_distribution(&self) -> &BTreeMap<u32, usize> {
+        &self.retry_distribution
+    }
+
+    /// Requests per second between each 10ms in which responses ended and the previous one since `start`
+    pub fn rps_samples(&self, start: Instant) -> Vec<f64> {
+        let Some(base) = self.ends.base else {
+            return Vec::new();
+        };
+        let offset = base.saturating_duration_since(start).as_secs_f64();
+        let interval = EndCounts::INTERVAL.as_secs_f64();
+        let mut prev = 0.0;
+        let mut samples = Vec::new();
+        for (i, &n) in self.ends.counts.iter().enumerate() {
+            if n == 0 {
+                continue;
+            }
+            // The end of the 10ms
+            let t = offset + (i + 1) as f64 * interval;
+            samples.push(n as f64 / (t - prev));
+            prev = t;
         }
-        dist
+        samples
+    }
+
+    /// Returns the width of the buckets and the buckets since the start. None unless created with `time_series`.
+    pub fn time_series(&self) -> Option<(Duration, Vec<TimeSeriesBucket>)> {
+        let time_series = self.time_series.as_ref()?;
+        Some((
+            time_series.interval,
+            time_series
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(i, bucket)| TimeSeriesBucket {
+                    offset: time_series.interval * i as u32,
+                    requests: bucket.requests,
+                    errors: bucket.errors,
+                    bytes: bucket.bytes,
+                    bytes_sent: bucket.bytes_sent,
+                    bytes_received: bucket.bytes_received,
+                    percentiles: bucket.latency.percentiles(&TIME_SERIES_PERCENTILES),
+                    max: if bucket.latency.len == 0 {
+                        f64::NAN
+                    } else {
+                        bucket.latency.max.as_secs_f64()
+                    },
+                })
+                .collect(),
+        ))
+    }
+
+    pub fn status_code_distribution(&self) -> BTreeMap<StatusCode, usize> {
+        self.status_code_distribution.clone()
     }
 
     pub fn dns_dialup_stat(&self) -> MinMaxMean {
-        self.success
+        self.dns_dialup.clone()
+    }
+
+    pub fn dns_lookup_stat(&self) -> MinMaxMean {
+        self.dns_lookup.clone()
+    }
+
+    pub fn tcp_connect_stat(&self) -> MinMaxMean {
+        self.tcp_connect.clone()
+    }
+
+    pub fn proxy_connect_stat(&self) -> MinMaxMean {
+        self.proxy_connect.clone()
+    }
+
+    pub fn tls_handshake_stat(&self) -> MinMaxMean {
+        self.tls_handshake.clone()
+    }
+
+    pub fn remote_ip_distribution(&self) -> BTreeMap<IpAddr, RemoteIpStat> {
+        self.remote_ip_distribution.clone()
+    }
+
+    pub fn content_encoding_distribution(&self) -> BTreeMap<ContentEncoding, ContentEncodingStat> {
+        self.content_encoding_distribution.clone()
+    }
+
+    /// Returns the number of requests by how many redirects they followed
+    pub fn redirect_distribution(&self) -> BTreeMap<usize, usize> {
+        self.redirect_distribution.clone()
+    }
+
+    /// Returns the stats of the first, second, ... redirects
+    pub fn redirect_hop_stats(&self) -> Vec<RedirectHopStat> {
+        self.redirect_hops.clone()
+    }
+
+    /// Returns the stats for each value of each `--track-header` header
+    pub fn header_value_distribution(
+        &self,
+        percentiles: &[f64],
+    ) -> BTreeMap<String, BTreeMap<TrackedValue, HeaderValueStat>> {
+        self.header_value_distribution
             .iter()
-            .filter_map(|r| {
-                r.connection_time
-                    .map(|ct| (ct.dialup - r.start).as_secs_f64())
+            .map(|(name, values)| {
+                (
+                    name.clone(),
+                    values
+                        .iter()
+                        .map(|(value, histogram)| {
+                            (
+                                value.clone(),
+                                HeaderValueStat {
+                                    count: histogram.len(),
+                                    percentiles: histogram.percentiles(percentiles),
+                                },
+                            )
+                        })
+                        .collect(),
+                )
             })
             .collect()
     }
 
-    pub fn dns_lookup_stat(&self) -> MinMaxMean {
-        self.success
+    /// Returns the stats for each URL or `--url-group` pattern. Empty unless the per-URL statistics are enabled.
+    pub fn url_distribution(&self) -> BTreeMap<String, UrlStat> {
+        self.url_distribution
             .iter()
-            .filter_map(|r| {
-                r.connection_time
-                    .map(|ct| (ct.dns_lookup - r.start).as_secs_f64())
+            .map(|(url_group, stat)| {
+                let count = stat.latency.len();
+                (
+                    url_group.clone(),
+                    UrlStat {
+                        count,
+                        error_rate: stat.not_successful as f64 / count as f64,
+                        percentiles: stat.latency.percentiles(&PERCENTILES),
+                    },
+                )
             })
             .collect()
     }
 
+    /// Returns the number of (resumed, all) TLS connections which completed the handshake
+    pub fn tls_resumption(&self) -> (usize, usize) {
+        (
+            self.connection_stat.tls_resumed,
+            self.connection_stat.tls_handshakes,
+        )
+    }
+
+    /// Returns the stats of the connections established
+    pub fn connection_stat(&self) -> ConnectionStat {
+        self.connection_stat.clone()
+    }
+
+    pub fn set_connection_stat(&mut self, connection_stat: ConnectionStat) {
+        if let Some(time_series) = &mut self.time_series {
+            time_series.set_socket_bytes(&connection_stat.bytes_series);
+        }
+        self.connection_stat = connection_stat;
+    }
+
     pub fn total_data(&self) -> usize {
-        self.success.iter().map(|r| r.len_bytes).sum()
+        self.total_data as usize
     }
 
     pub fn size_per_request(&self) -> Option<u64> {
-        self.success
-            .iter()
-            .map(|r| r.len_bytes as u64)
-            .sum::<u64>()
-            .checked_div(self.success.len() as u64)
+        self.total_data.checked_div(self.success_count as u64)
     }
 
-    pub fn duration_all_statistics(&self) -> Statistics {
-        let mut data = self
-            .success
-            .iter()
-            .map(|r| r.duration().as_secs_f64())
-            .collect::<Vec<_>>();
-        Statistics::new(&mut data)
+    pub fn throughput_stat(&self) -> ThroughputStat {
+        self.traffic
     }
 
-    pub fn duration_successful_statistics(&self) -> Statistics {
-        let mut data = self
-            .success
-            .iter()
-            .filter(|r| r.status.is_success())
-            .map(|r| r.duration().as_secs_f64())
-            .collect::<Vec<_>>();
+    /// Returns (percentile, bytes) of the body sizes of the responses. Empty if there's no response.
+    pub fn response_size_percentiles(&self, percentiles: &[f64]) -> Vec<(f64, u64)> {
+        self.response_size.percentiles(percentiles)
+    }
+
+    pub fn duration_all_statistics(&self, config: &StatsConfig) -> Statistics {
+        let mut all = self.latency_successful.clone();
+        all.merge(&self.latency_not_successful);
+        Statistics::new(&all, config)
+    }
+
+    pub fn duration_successful_statistics(&self, config: &StatsConfig) -> Statistics {
+        Statistics::new(&self.latency_successful, config)
+    }
+
+    pub fn duration_not_successful_statistics(&self, config: &StatsConfig) -> Statistics {
+        Statistics::new(&self.latency_not_successful, config)
+    }
 
-        Statistics::new(&mut data)
+    /// Time to first byte of the responses with a body
+    pub fn ttfb_statistics(&self, config: &StatsConfig) -> Statistics {
+        Statistics::new(&self.ttfb, config)
     }
 
-    pub fn duration_not_successful_statistics(&self) -> Statistics {
-        let mut data = self
-            .success
+    /// Time from the first byte to the end of the responses with a body
+    pub fn download_time_statistics(&self, config: &StatsConfig) -> Statistics {
+        Statistics::new(&self.download_time, config)
+    }
+
+    /// Returns the response time statistics of each status code
+    pub fn status_code_statistics(&self, config: &StatsConfig) -> BTreeMap<StatusCode, Statistics> {
+        self.status_code_latency
             .iter()
-            .filter(|r| !r.status.is_success())
-            .map(|r| r.duration().as_secs_f64())
-            .collect::<Vec<_>>();
+            .map(|(status, histogram)| (*status, Statistics::new(histogram, config)))
+            .collect()
+    }
 
-        Statistics::new(&mut data)
+    /// Returns the time to failure statistics of each kind of errors.
+    /// Errors without a request sent, such as by the deadline, aren't included.
+    pub fn error_statistics(&self, config: &StatsConfig) -> BTreeMap<ErrorKind, Statistics> {
+        self.error_latency
+            .iter()
+            .map(|(kind, histogram)| (*kind, Statistics::new(histogram, config)))
+            .collect()
     }
 }
 
+/// Returns the stat of `url_group`, or of `OTHER_URL_GROUP` if `dist` already has too many other groups
+fn url_group_stat<'a>(
+    dist: &'a mut BTreeMap<String, UrlLatency>,
+    url_group: &str,
+) -> &'a mut UrlLatency {
+    let groups = dist.len() - usize::from(dist.contains_key(OTHER_URL_GROUP));
+    let url_group = if dist.contains_key(url_group) || groups < MAX_URL_GROUPS {
+        url_group
+    } else {
+        OTHER_URL_GROUP
+    };
+    // Avoid allocating the key for every request
+    if !dist.contains_key(url_group) {
+        dist.insert(url_group.to_string(), UrlLatency::default());
+    }
+    dist.get_mut(url_group).unwrap()
+}
+
+fn merge_remote_ip_stat(
+    dist: &mut BTreeMap<IpAddr, RemoteIpStat>,
+    remote_ip: IpAddr,
+    stat: RemoteIpStat,
+) {
+    dist.entry(remote_ip)
+        .and_modify(|s| {
+            s.count += stat.count;
+            s.first_seen = s.first_seen.min(stat.first_seen);
+            s.last_seen = s.last_seen.max(stat.last_seen);
+        })
+        .or_insert(stat);
+}
+
+fn merge_error_kind_stat(
+    dist: &mut BTreeMap<ErrorKind, ErrorKindStat>,
+    kind: ErrorKind,
+    stat: ErrorKindStat,
+) {
+    dist.entry(kind)
+        .and_modify(|s| {
+            s.count += stat.count;
+            if stat.first_seen < s.first_seen {
+                s.first_seen = stat.first_seen;
+                s.sample.clone_from(&stat.sample);
+            }
+            s.last_seen = s.last_seen.max(stat.last_seen);
+        })
+        .or_insert(stat);
+}
+
+fn merge_content_encoding_stat(
+    dist: &mut BTreeMap<ContentEncoding, ContentEncodingStat>,
+    content_encoding: ContentEncoding,
+    stat: ContentEncodingStat,
+) {
+    dist.entry(content_encoding)
+        .and_modify(|s| {
+            s.count += stat.count;
+            s.len_bytes += stat.len_bytes;
+            s.decoded_len_bytes = s
+                .decoded_len_bytes
+                .zip(stat.decoded_len_bytes)
+                .map(|(a, b)| a + b);
+        })
+        .or_insert(stat);
+}
+
 #[cfg(test)]
 mod tests {
     use float_cmp::assert_approx_eq;
@@ -191,12 +1276,14 @@ mod tests {
 
     use super::*;
     use crate::client::{ClientError, ConnectionTime, RequestResult};
+    use hyper::http;
     use std::time::{Duration, Instant};
 
     fn build_mock_request_result(
         status: StatusCode,
         request_time: u64,
         connection_time_dns_lookup: u64,
+        connection_time_tcp_connect: u64,
         connection_time_dialup: u64,
         first_byte: u64,
         size: usize,
@@ -207,12 +1294,15 @@ mod tests {
             start_latency_correction: None,
             start: now,
             connection_time: Some(ConnectionTime {
-                dns_lookup: now
-                    .checked_add(Duration::from_millis(connection_time_dns_lookup))
-                    .unwrap(),
                 dialup: now
                     .checked_add(Duration::from_millis(connection_time_dialup))
                     .unwrap(),
+                ..ConnectionTime::new(
+                    now.checked_add(Duration::from_millis(connection_time_dns_lookup))
+                        .unwrap(),
+                    now.checked_add(Duration::from_millis(connection_time_tcp_connect))
+                        .unwrap(),
+                )
             }),
             first_byte: Some(now.checked_add(Duration::from_millis(first_byte)).unwrap()),
             end: now
@@ -220,6 +1310,16 @@ mod tests {
                 .unwrap(),
             status,
             len_bytes: size,
+            content_encoding: ContentEncoding::Identity,
+            decoded_len_bytes: None,
+            tracked_headers: Vec::new(),
+            redirects: Vec::new(),
+            retries: 0,
+            first_attempt_start: None,
+            url_group: None,
+            request_bytes: 0,
+            response_header_bytes: 0,
+            remote_ip: None,
         })
     }
 
@@ -230,6 +1330,7 @@ mod tests {
             StatusCode::OK,
             1000,
             200,
+            220,
             50,
             300,
             100,
@@ -238,6 +1339,7 @@ mod tests {
             StatusCode::BAD_REQUEST,
             100000,
             250,
+            280,
             100,
             400,
             200,
@@ -246,6 +1348,7 @@ mod tests {
             StatusCode::INTERNAL_SERVER_ERROR,
             1000000,
             300,
+            340,
             150,
             500,
             300,
@@ -259,6 +1362,200 @@ mod tests {
         assert_approx_eq!(f64, res.success_rate(), 1.0);
     }
 
+    #[test]
+    fn test_calculate_assertion_failures() {
+        let mut res = build_mock_request_results();
+        let mut other = ResultData::default();
+        other.push(Err(ClientError::AssertionFailed {
+            status: StatusCode::OK,
+            duration: Duration::from_millis(1),
+            error: crate::assertion::AssertionError::BodySize(10),
+        }));
+        other.push(Err(ClientError::Timeout));
+        res.merge(other);
+        assert_eq!(res.assertion_failures(), 1);
+        assert_eq!(
+            res.error_kinds()[&ErrorKind::Validation].sample,
+            "Assertion failed: body is larger than 10 bytes"
+        );
+        assert_eq!(res.error_distribution()[&ErrorKind::Validation], 1);
+        assert_approx_eq!(f64, res.success_rate(), 0.6);
+    }
+
+    #[test]
+    fn test_calculate_redirects() {
+        let mut res = build_mock_request_results();
+        let hop = |status, millis| crate::client::RedirectHop {
+            status,
+            duration: Duration::from_millis(millis),
+        };
+        for redirects in [
+            vec![hop(StatusCode::FOUND, 10)],
+            vec![hop(StatusCode::SEE_OTHER, 30), hop(StatusCode::FOUND, 20)],
+        ] {
+            let mut result = build_mock_request_result(StatusCode::OK, 100, 0, 0, 0, 0, 0).unwrap();
+            result.redirects = redirects;
+            res.push(Ok(result));
+        }
+
+        assert_eq!(
+            res.redirect_distribution(),
+            BTreeMap::from([(0, 3), (1, 1), (2, 1)])
+        );
+        let hops = res.redirect_hop_stats();
+        assert_eq!(hops.len(), 2);
+        assert_eq!(
+            hops[0].status_distribution,
+            BTreeMap::from([(StatusCode::FOUND, 1), (StatusCode::SEE_OTHER, 1)])
+        );
+        assert_approx_eq!(f64, hops[0].latency.mean(), 0.02);
+        assert_eq!(
+            hops[1].status_distribution,
+            BTreeMap::from([(StatusCode::FOUND, 1)])
+        );
+        assert_approx_eq!(f64, hops[1].latency.max(), 0.02);
+    }
+
+    #[test]
+    fn test_calculate_retries() {
+        let mut res = ResultData::default();
+        let now = Instant::now();
+        for retries in [0, 0, 1, 2] {
+            let mut result = build_mock_request_result(StatusCode::OK, 100, 0, 0, 0, 0, 0).unwrap();
+            result.retries = retries;
+            if retries > 0 {
+                result.start = now;
+                result.end = now + Duration::from_millis(100);
+                result.first_attempt_start = Some(now - Duration::from_millis(900));
+            }
+            res.push(Ok(result));
+        }
+        res.push(Err(ClientError::Failed {
+            retries: 2,
+            duration: Some(Duration::from_secs(3)),
+            end: now,
+            error: Box::new(ClientError::Timeout),
+        }));
+
+        assert_eq!(
+            res.retry_distribution(),
+            &BTreeMap::from([(0, 2), (1, 1), (2, 2)])
+        );
+        assert_eq!(res.error_distribution()[&ErrorKind::Timeout], 1);
+        assert_approx_eq!(f64, res.success_rate(), 0.8);
+        assert_approx_eq!(f64, res.first_attempt_success_rate(), 0.4);
+        assert_approx_eq!(f64, res.latency_stat().max(), 1.0);
+    }
+
+    #[test]
+    fn test_calculate_status_code_and_error_statistics() {
+        let mut res = build_mock_request_results();
+        res.push(build_mock_request_result(
+            StatusCode::OK,
+            3000,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ));
+        res.push(Err(ClientError::Failed {
+            retries: 0,
+            duration: Some(Duration::from_millis(500)),
+            end: Instant::now(),
+            error: Box::new(ClientError::Timeout),
+        }));
+        res.push(Err(ClientError::Failed {
+            retries: 0,
+            duration: Some(Duration::from_millis(1500)),
+            end: Instant::now(),
+            error: Box::new(ClientError::Timeout),
+        }));
+        // Without time to failure
+        res.push(Err(ClientError::Deadline.unattempted()));
+
+        let status_stats = res.status_code_statistics(&StatsConfig::default());
+        assert_eq!(status_stats.len(), 3);
+        let ok = &status_stats[&StatusCode::OK];
+        assert_approx_eq!(f64, ok.percentiles[0].1, 1.0);
+        assert_approx_eq!(f64, ok.percentiles[8].1, 3.0);
+        assert_eq!(ok.histogram.iter().map(|(_, n)| n).sum::<usize>(), 2);
+        assert_approx_eq!(
+            f64,
+            status_stats[&StatusCode::INTERNAL_SERVER_ERROR].percentiles[4].1,
+            1000.0
+        );
+
+        let error_stats = res.error_statistics(&StatsConfig::default());
+        assert_eq!(error_stats.len(), 1);
+        let timeout = &error_stats[&ErrorKind::Timeout];
+        assert_approx_eq!(f64, timeout.percentiles[0].1, 0.5);
+        assert_approx_eq!(f64, timeout.percentiles[8].1, 1.5);
+        assert_eq!(res.error_distribution()[&ErrorKind::Timeout], 2);
+    }
+
+    #[test]
+    fn test_calculate_error_kinds() {
+        let mut res = build_mock_request_results();
+        let now = Instant::now();
+        res.push(Err(ClientError::Failed {
+            retries: 0,
+            duration: Some(Duration::from_millis(1)),
+            end: now + Duration::from_millis(20),
+            error: Box::new(ClientError::IoError(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                "Connection refused (os error 111) 127.0.0.1:1",
+            ))),
+        }));
+        // Failed earlier but aggregated later
+        res.push(Err(ClientError::Failed {
+            retries: 0,
+            duration: Some(Duration::from_millis(1)),
+            end: now + Duration::from_millis(10),
+            error: Box::new(ClientError::IoError(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                "Connection refused (os error 111) 127.0.0.1:2",
+            ))),
+        }));
+        res.push(Err(ClientError::Timeout));
+        res.push(Err(ClientError::Deadline));
+        res.push(Err(ClientError::Deadline));
+
+        let kinds = res.error_kinds();
+        assert_eq!(kinds.len(), 3);
+        let refused = &kinds[&ErrorKind::ConnectRefused];
+        assert_eq!(refused.count, 2);
+        assert_eq!(
+            refused.sample,
+            "Connection refused (os error 111) 127.0.0.1:2"
+        );
+        assert_eq!(refused.first_seen, now + Duration::from_millis(10));
+        assert_eq!(refused.last_seen, now + Duration::from_millis(20));
+        assert_eq!(kinds[&ErrorKind::Timeout].count, 1);
+        assert_eq!(kinds[&ErrorKind::Deadline].count, 2);
+        assert_eq!(res.error_distribution().len(), 3);
+        // Deadline errors are ignored
+        assert_approx_eq!(f64, res.success_rate(), 0.5);
+    }
+
+    #[test]
+    fn test_calculate_ttfb_and_download_time() {
+        let mut res = build_mock_request_results();
+        // Empty body
+        let mut result = build_mock_request_result(StatusCode::OK, 10, 0, 0, 0, 0, 0).unwrap();
+        result.first_byte = None;
+        res.push(Ok(result));
+
+        let ttfb = res.ttfb_statistics(&StatsConfig::default());
+        assert_eq!(ttfb.histogram.iter().map(|(_, n)| n).sum::<usize>(), 3);
+        assert_approx_eq!(f64, ttfb.percentiles[0].1, 0.3);
+        assert_approx_eq!(f64, ttfb.percentiles[8].1, 0.5);
+
+        let download_time = res.download_time_statistics(&StatsConfig::default());
+        assert_approx_eq!(f64, download_time.percentiles[0].1, 0.7);
+        assert_approx_eq!(f64, download_time.percentiles[8].1, 999.5);
+    }
+
     #[test]
     fn test_calculate_slowest_request() {
         let res = build_mock_request_results();
@@ -318,4 +1615,477 @@ mod tests {
         let res = build_mock_request_results();
         assert_approx_eq!(f64, res.dns_lookup_stat().max(), 0.3);
     }
+
+    #[test]
+    fn test_calculate_connection_times_tcp_connect() {
+        let res = build_mock_request_results();
+        let tcp_connect_stat = res.tcp_connect_stat();
+        assert_approx_eq!(f64, tcp_connect_stat.mean(), 0.03);
+        assert_approx_eq!(f64, tcp_connect_stat.min(), 0.02);
+        assert_approx_eq!(f64, tcp_connect_stat.max(), 0.04);
+    }
+
+    #[test]
+    fn test_calculate_connection_times_tls_handshake() {
+        let mut res = build_mock_request_results();
+        assert!(res.tls_handshake_stat().mean().is_nan());
+        assert!(res.proxy_connect_stat().mean().is_nan());
+
+        for proxy_connect in [None, Some(Duration::from_millis(30))] {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, 100, 0, 10, 50, 0, 0).unwrap();
+            let start = result.start;
+            let connection_time = result.connection_time.as_mut().unwrap();
+            connection_time.proxy_connect = proxy_connect.map(|d| start + d);
+            connection_time.tls_handshake = Some(connection_time.dialup);
+            res.push(Ok(result));
+        }
+
+        let tls_handshake_stat = res.tls_handshake_stat();
+        assert_approx_eq!(f64, tls_handshake_stat.min(), 0.02);
+        assert_approx_eq!(f64, tls_handshake_stat.max(), 0.04);
+        let proxy_connect_stat = res.proxy_connect_stat();
+        assert_approx_eq!(f64, proxy_connect_stat.mean(), 0.02);
+    }
+
+    #[test]
+    fn test_calculate_remote_ip_distribution() {
+        let mut res = build_mock_request_results();
+        assert!(res.remote_ip_distribution().is_empty());
+
+        let now = Instant::now();
+        let blue: IpAddr = "10.0.0.1".parse().unwrap();
+        let green: IpAddr = "10.0.0.2".parse().unwrap();
+        for (start, remote_ip) in [(0, blue), (10, blue), (20, green), (30, green), (40, green)] {
+            let mut result = build_mock_request_result(StatusCode::OK, 5, 0, 0, 0, 0, 0).unwrap();
+            let start = now + Duration::from_millis(start);
+            result.start = start;
+            result.end = start + Duration::from_millis(5);
+            result.connection_time = Some(ConnectionTime {
+                remote_ip: Some(remote_ip),
+                ..ConnectionTime::new(start, start)
+            });
+            result.remote_ip = Some(remote_ip);
+            res.push(Ok(result));
+        }
+
+        let dist = res.remote_ip_distribution();
+        assert_eq!(dist.len(), 2);
+        assert_eq!(dist[&blue].count, 2);
+        assert_eq!(dist[&blue].last_seen, now + Duration::from_millis(10));
+        assert_eq!(dist[&green].count, 3);
+        assert_eq!(dist[&green].first_seen, now + Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_calculate_header_value_distribution() {
+        let mut res = ResultData::default();
+        let name = http::HeaderName::from_static("x-cache");
+        for (value, request_time) in [
+            (Some("HIT"), 10),
+            (Some("HIT"), 20),
+            (Some("MISS"), 300),
+            (None, 50),
+        ] {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, request_time, 200, 220, 50, 300, 0)
+                    .unwrap();
+            result.tracked_headers =
+                vec![(name.clone(), value.map(http::HeaderValue::from_static))];
+            res.push(Ok(result));
+        }
+
+        let dist = res.header_value_distribution(&PERCENTILES);
+        assert_eq!(dist.len(), 1);
+        let x_cache = &dist["x-cache"];
+        assert_eq!(x_cache.len(), 3);
+        let hit = &x_cache[&TrackedValue::Value("HIT".to_string())];
+        assert_eq!(hit.count, 2);
+        assert_approx_eq!(f64, hit.percentiles[0].1, 0.01);
+        assert_approx_eq!(f64, hit.percentiles[8].1, 0.02);
+        assert_eq!(x_cache[&TrackedValue::Value("MISS".to_string())].count, 1);
+        assert_eq!(x_cache[&TrackedValue::Missing].count, 1);
+    }
+
+    #[test]
+    fn test_header_value_distribution_cap() {
+        let name = http::HeaderName::from_static("x-request-id");
+        let push = |res: &mut ResultData, value: String| {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, 10, 200, 220, 50, 300, 0).unwrap();
+            result.tracked_headers = vec![(
+                name.clone(),
+                Some(http::HeaderValue::from_str(&value).unwrap()),
+            )];
+            res.push(Ok(result));
+        };
+        let mut res = ResultData::default();
+        let mut other = ResultData::default();
+        for i in 0..MAX_TRACKED_VALUES + 10 {
+            push(&mut res, i.to_string());
+            push(&mut other, format!("other-{i}"));
+        }
+        // Known values are still counted as themselves
+        push(&mut res, "0".to_string());
+        res.merge(other);
+
+        let dist = res.header_value_distribution(&[50.0]);
+        let values = &dist["x-request-id"];
+        assert_eq!(values.len(), MAX_TRACKED_VALUES + 1);
+        assert_eq!(values[&TrackedValue::Value("0".to_string())].count, 2);
+        assert_eq!(
+            values[&TrackedValue::Other].count,
+            10 + MAX_TRACKED_VALUES + 10
+        );
+        assert_approx_eq!(f64, values[&TrackedValue::Other].percentiles[0].1, 0.01);
+    }
+
+    #[test]
+    fn test_calculate_content_encoding_distribution() {
+        let mut res = ResultData::default();
+        for (content_encoding, len_bytes, decoded_len_bytes) in [
+            (ContentEncoding::Gzip, 100, Some(1000)),
+            (ContentEncoding::Gzip, 200, Some(1500)),
+            (ContentEncoding::Identity, 300, Some(300)),
+            (ContentEncoding::Other, 400, None),
+        ] {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, 1000, 200, 220, 50, 300, 0).unwrap();
+            result.len_bytes = len_bytes;
+            result.content_encoding = content_encoding;
+            result.decoded_len_bytes = decoded_len_bytes;
+            res.push(Ok(result));
+        }
+
+        let dist = res.content_encoding_distribution();
+        assert_eq!(dist.len(), 3);
+        let gzip = &dist[&ContentEncoding::Gzip];
+        assert_eq!(gzip.count, 2);
+        assert_eq!(gzip.len_bytes, 300);
+        assert_eq!(gzip.decoded_len_bytes, Some(2500));
+        assert_approx_eq!(f64, gzip.ratio().unwrap(), 2500.0 / 300.0);
+        assert_approx_eq!(f64, dist[&ContentEncoding::Identity].ratio().unwrap(), 1.0);
+        assert!(dist[&ContentEncoding::Other].ratio().is_none());
+    }
+
+    #[test]
+    fn test_calculate_url_distribution() {
+        let mut res = ResultData::default();
+        let mut other = ResultData::default();
+        for (i, (url_group, status, request_time)) in [
+            ("http://127.0.0.1/a", StatusCode::OK, 10),
+            ("http://127.0.0.1/a", StatusCode::NOT_FOUND, 20),
+            ("/b/", StatusCode::OK, 30),
+            ("/b/", StatusCode::OK, 40),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut result =
+                build_mock_request_result(status, request_time, 0, 0, 0, 0, 0).unwrap();
+            result.url_group = Some(url_group.to_string());
+            if i % 2 == 0 { &mut res } else { &mut other }.push(Ok(result));
+        }
+        // Not counted
+        res.push(build_mock_request_result(StatusCode::OK, 50, 0, 0, 0, 0, 0));
+        res.merge(other);
+
+        let dist = res.url_distribution();
+        assert_eq!(dist.len(), 2);
+        let a = &dist["http://127.0.0.1/a"];
+        assert_eq!(a.count, 2);
+        assert_approx_eq!(f64, a.error_rate, 0.5);
+        assert_approx_eq!(f64, a.percentiles[0].1, 0.01);
+        assert_approx_eq!(f64, a.percentiles[8].1, 0.02);
+        let b = &dist["/b/"];
+        assert_eq!(b.count, 2);
+        assert_approx_eq!(f64, b.error_rate, 0.0);
+    }
+
+    #[test]
+    fn test_url_distribution_capped() {
+        let mut res = ResultData::default();
+        let mut other = ResultData::default();
+        for i in 0..MAX_URL_GROUPS + 10 {
+            let mut result = build_mock_request_result(StatusCode::OK, 10, 0, 0, 0, 0, 0).unwrap();
+            result.url_group = Some(format!("/{i}"));
+            res.push(Ok(result.clone()));
+            other.push(Ok(result));
+        }
+        res.merge(other);
+
+        let dist = res.url_distribution();
+        assert_eq!(dist.len(), MAX_URL_GROUPS + 1);
+        assert_eq!(dist["/0"].count, 2);
+        assert_eq!(dist[OTHER_URL_GROUP].count, 20);
+    }
+
+    #[test]
+    fn test_duration_histogram() {
+        let mut histogram = DurationHistogram::default();
+        assert!(histogram.percentiles(&PERCENTILES)[0].1.is_nan());
+        for millis in 1..=1000 {
+            histogram.record(Duration::from_millis(millis));
+        }
+        assert_eq!(histogram.len(), 1000);
+
+        let percentiles = histogram.percentiles(&PERCENTILES);
+        // The min and max are exact
+        assert_approx_eq!(f64, percentiles[0].1, 0.1, epsilon = 0.0001);
+        assert_approx_eq!(f64, percentiles[2].1, 0.5, epsilon = 0.0005);
+        assert_approx_eq!(f64, percentiles[8].1, 1.0);
+
+        let bins = histogram.histogram(&HistogramBins::Linear(11));
+        assert_eq!(bins.len(), 11);
+        assert_approx_eq!(f64, bins[0].0, 0.001);
+        assert_approx_eq!(f64, bins[10].0, 1.0);
+        assert_eq!(bins.iter().map(|(_, n)| n).sum::<usize>(), 1000);
+    }
+
+    #[test]
+    fn test_merge() {
+        let results = || {
+            [
+                (StatusCode::OK, 1000, 100),
+                (StatusCode::OK, 500, 50),
+                (StatusCode::NOT_FOUND, 200, 0),
+                (StatusCode::INTERNAL_SERVER_ERROR, 100000, 300),
+            ]
+            .map(|(status, request_time, size)| {
+                build_mock_request_result(status, request_time, 200, 220, 50, 300, size)
+            })
+        };
+        let mut all = ResultData::default();
+        let mut merged = ResultData::default();
+        for _ in 0..3 {
+            let mut other = ResultData::default();
+            for result in results() {
+                all.push(result);
+            }
+            for result in results() {
+                other.push(result);
+            }
+            merged.merge(other);
+        }
+
+        assert_eq!(merged.len(), all.len());
+        assert_eq!(merged.total_data(), all.total_data());
+        assert_eq!(
+            merged.status_code_distribution(),
+            all.status_code_distribution()
+        );
+        assert_approx_eq!(f64, merged.latency_stat().mean(), all.latency_stat().mean());
+        assert_approx_eq!(
+            f64,
+            merged.dns_lookup_stat().max(),
+            all.dns_lookup_stat().max()
+        );
+        assert_eq!(
+            merged
+                .duration_all_statistics(&StatsConfig::default())
+                .percentiles,
+            all.duration_all_statistics(&StatsConfig::default())
+                .percentiles
+        );
+        assert_eq!(
+            merged
+                .duration_not_successful_statistics(&StatsConfig::default())
+                .histogram,
+            all.duration_not_successful_statistics(&StatsConfig::default())
+                .histogram
+        );
+    }
+
+    #[test]
+    fn test_keep_results() {
+        let mut res = build_mock_request_results();
+        assert!(res.success().is_empty());
+        res.merge(build_mock_request_results());
+        assert!(res.success().is_empty());
+
+        let mut res = ResultData::new(ResultDataOptions {
+            keep_results: true,
+            ..Default::default()
+        });
+        res.push(build_mock_request_result(
+            StatusCode::OK,
+            1000,
+            200,
+            220,
+            50,
+            300,
+            100,
+        ));
+        res.merge(build_mock_request_results());
+        assert_eq!(res.success().len(), 1);
+        let mut other = ResultData::new(ResultDataOptions {
+            keep_results: true,
+            ..Default::default()
+        });
+        other.push(build_mock_request_result(
+            StatusCode::OK,
+            1000,
+            200,
+            220,
+            50,
+            300,
+            100,
+        ));
+        res.merge(other);
+        assert_eq!(res.success().len(), 2);
+        assert_eq!(res.len(), 5);
+    }
+
+    #[test]
+    fn test_latency_correction_stat() {
+        let mut res = build_mock_request_results();
+        assert!(
+            res.latency_correction_stat(&StatsConfig::default())
+                .is_none()
+        );
+
+        let mut res2 = ResultData::default();
+        for (queued, service) in [(100, 1000), (300, 2000)] {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, queued + service, 0, 0, 0, 0, 0).unwrap();
+            result.start_latency_correction = Some(result.start);
+            result.start += Duration::from_millis(queued);
+            res2.push(Ok(result));
+        }
+        res.merge(res2);
+
+        let stat = res
+            .latency_correction_stat(&StatsConfig::default())
+            .unwrap();
+        assert_approx_eq!(f64, stat.service_time.min(), 1.0);
+        assert_approx_eq!(f64, stat.service_time.max(), 2.0);
+        assert_approx_eq!(f64, stat.service_time.mean(), 1.5);
+        assert_approx_eq!(f64, stat.queueing_delay.min(), 0.1);
+        assert_approx_eq!(f64, stat.queueing_delay.max(), 0.3);
+        assert_approx_eq!(f64, stat.queueing_delay.mean(), 0.2);
+        assert_eq!(
+            stat.service_time_statistics
+                .histogram
+                .iter()
+                .map(|(_, n)| n)
+                .sum::<usize>(),
+            2
+        );
+        // Corrected latency includes the queueing delay
+        assert_approx_eq!(f64, res.latency_stat().max(), 1000.0);
+    }
+
+    #[test]
+    fn test_rps_samples() {
+        let mut res = ResultData::default();
+        let start = Instant::now();
+        assert!(res.rps_samples(start).is_empty());
+        // Counted in each 10ms since the first end
+        for end in [12, 15, 1005] {
+            let mut result = build_mock_request_result(StatusCode::OK, 0, 0, 0, 0, 0, 0).unwrap();
+            result.end = start + Duration::from_millis(end);
+            res.push(Ok(result));
+        }
+        let rps = res.rps_samples(start);
+        assert_eq!(rps.len(), 2);
+        assert_approx_eq!(f64, rps[0], 2.0 / 0.022, epsilon = 1.0);
+        assert_approx_eq!(f64, rps[1], 1.0 / 0.99, epsilon = 0.01);
+    }
+
+    #[test]
+    fn test_time_series() {
+        let start = Instant::now();
+        let options = ResultDataOptions {
+            time_series: Some((start, Duration::from_secs(1))),
+            ..Default::default()
+        };
+        let mut res = ResultData::new(options);
+        let mut other = ResultData::new(options);
+        for (end, request_time, size) in [(100, 10, 100), (900, 20, 200), (2500, 1000, 50)] {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, request_time, 0, 0, 0, 0, size).unwrap();
+            result.start = start + Duration::from_millis(end - request_time);
+            result.end = start + Duration::from_millis(end);
+            other.push(Ok(result));
+        }
+        // Recorded now but counted when it failed
+        res.push(Err(ClientError::Failed {
+            retries: 0,
+            duration: Some(Duration::from_millis(200)),
+            end: start + Duration::from_millis(2200),
+            error: Box::new(ClientError::Timeout),
+        }));
+        res.merge(other);
+
+        let (interval, buckets) = res.time_series().unwrap();
+        assert_eq!(interval, Duration::from_secs(1));
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].offset, Duration::ZERO);
+        assert_eq!(buckets[0].requests, 2);
+        assert_eq!(buckets[0].errors, 0);
+        assert_eq!(buckets[0].bytes, 300);
+        assert_approx_eq!(f64, buckets[0].percentiles[0].1, 0.01, epsilon = 0.0001);
+        assert_approx_eq!(f64, buckets[0].percentiles[2].1, 0.02, epsilon = 0.0002);
+        assert_approx_eq!(f64, buckets[0].max, 0.02);
+        assert_eq!(buckets[1].requests, 0);
+        assert!(buckets[1].max.is_nan());
+        assert!(buckets[1].percentiles[0].1.is_nan());
+        assert_eq!(buckets[2].offset, Duration::from_secs(2));
+        assert_eq!(buckets[2].requests, 2);
+        assert_eq!(buckets[2].errors, 1);
+        assert_approx_eq!(f64, buckets[2].max, 1.0);
+
+        res.set_connection_stat(ConnectionStat {
+            bytes_series: vec![(10, 100), (0, 0), (20, 200), (30, 300)],
+            ..Default::default()
+        });
+        let (_, buckets) = res.time_series().unwrap();
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(
+            (buckets[0].bytes_sent, buckets[0].bytes_received),
+            (10, 100)
+        );
+        assert_eq!(
+            (buckets[2].bytes_sent, buckets[2].bytes_received),
+            (20, 200)
+        );
+        assert_eq!(buckets[3].requests, 0);
+        assert_eq!(buckets[3].bytes_sent, 30);
+
+        assert!(ResultData::default().time_series().is_none());
+    }
+
+    #[test]
+    fn test_throughput() {
+        let mut res = ResultData::default();
+        let mut other = ResultData::default();
+        for size in 1..=100 {
+            let mut result =
+                build_mock_request_result(StatusCode::OK, 10, 0, 0, 0, 0, size * 10).unwrap();
+            result.request_bytes = 50;
+            result.response_header_bytes = 30;
+            if size % 2 == 0 {
+                res.push(Ok(result));
+            } else {
+                other.push(Ok(result));
+            }
+        }
+        res.push(Err(ClientError::Timeout));
+        res.merge(other);
+
+        let stat = res.throughput_stat();
+        assert_eq!(stat.request_bytes, 5000);
+        assert_eq!(stat.response_header_bytes, 3000);
+        assert_eq!(stat.request_size(), Some(50));
+        assert_eq!(stat.response_header_size(), Some(30));
+        assert_eq!(ThroughputStat::default().request_size(), None);
+
+        let percentiles = res.response_size_percentiles(&[10.0, 50.0, 100.0]);
+        assert_eq!(percentiles, vec![(10.0, 100), (50.0, 500), (100.0, 1000)]);
+        assert!(
+            ResultData::default()
+                .response_size_percentiles(&[50.0])
+                .is_empty()
+        );
+    }
 }
@@ -0,0 +1,132 @@
+use hyper::http::StatusCode;
+use std::{str::FromStr, time::Duration};
+
+use crate::client::{ClientError, RequestResult, is_too_many_open_files};
+
+/// A condition to retry a request on, specified by `--retry-on`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryCondition {
+    /// Failures to connect or of the connection
+    Connection,
+    /// `-t` timeout of an attempt
+    Timeout,
+    /// A response with the status
+    Status(StatusCode),
+}
+
+impl FromStr for RetryCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "connection" => Ok(Self::Connection),
+            "timeout" => Ok(Self::Timeout),
+            s => StatusCode::from_str(s).map(Self::Status).map_err(|_| {
+                format!("--retry-on must be connection, timeout or a status code, but got {s}")
+            }),
+        }
+    }
+}
+
+impl RetryCondition {
+    fn matches(&self, res: &Result<RequestResult, ClientError>) -> bool {
+        match (self, res) {
+            (Self::Status(status), Ok(result)) => result.status == *status,
+            // The status is known even if an assertion rejected the response
+            (Self::Status(status), Err(ClientError::AssertionFailed { status: code, .. })) => {
+                status == code
+            }
+            (Self::Timeout, Err(ClientError::Timeout)) => true,
+            (
+                Self::Connection,
+                Err(
+                    ClientError::IoError(_)
+                    | ClientError::HyperError(_)
+                    | ClientError::ResolveError(_)
+                    | ClientError::ConnectTimeout,
+                ),
+            ) => !is_too_many_open_files(res),
+            _ => false,
+        }
+    }
+}
+
+/// How requests are retried, specified by `--retries`, `--retry-on` and `--retry-backoff`
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 0 disables retries
+    pub retries: u32,
+    pub conditions: Vec<RetryCondition>,
+    /// Delay before the first retry, doubled for each following retry
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            conditions: Vec::new(),
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether to retry after `retries` retries have been done
+    pub fn should_retry(&self, res: &Result<RequestResult, ClientError>, retries: u32) -> bool {
+        retries < self.retries && self.conditions.iter().any(|c| c.matches(res))
+    }
+
+    /// Delay before the `retry`th retry, counted from 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assertion::AssertionError;
+
+    #[test]
+    fn test_retry_condition() {
+        let policy = RetryPolicy {
+            retries: 2,
+            conditions: "connection,timeout,503"
+                .split(',')
+                .map(|s| s.parse().unwrap())
+                .collect(),
+            ..Default::default()
+        };
+        let timeout = Err(ClientError::Timeout);
+        assert!(policy.should_retry(&timeout, 0));
+        assert!(policy.should_retry(&timeout, 1));
+        assert!(!policy.should_retry(&timeout, 2));
+        assert!(policy.should_retry(&Err(ClientError::ConnectTimeout), 0));
+        assert!(policy.should_retry(
+            &Err(ClientError::AssertionFailed {
+                status: StatusCode::SERVICE_UNAVAILABLE,
+                duration: Duration::from_millis(1),
+                error: AssertionError::Status(503, "2xx".to_string()),
+            }),
+            0
+        ));
+        assert!(!policy.should_retry(&Err(ClientError::Deadline), 0));
+        assert!(!policy.should_retry(
+            &Err(ClientError::IoError(std::io::Error::from_raw_os_error(
+                libc::EMFILE
+            ))),
+            0
+        ));
+        assert!("connect".parse::<RetryCondition>().is_err());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+    }
+}
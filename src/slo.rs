@@ -0,0 +1,273 @@
+use crate::result_data::{ResultData, StatsConfig};
+use std::{str::FromStr, time::Duration};
+
+/// What an `--assert` SLO checks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    /// Response time percentile like `p99`
+    Percentile(f64),
+    /// Average response time
+    Average,
+    /// Slowest response time
+    Slowest,
+    /// Same as the success rate of the summary
+    SuccessRate,
+    ErrorRate,
+    /// Same as the requests per second of the summary
+    Rps,
+}
+
+impl Metric {
+    fn is_latency(&self) -> bool {
+        matches!(self, Self::Percentile(_) | Self::Average | Self::Slowest)
+    }
+
+    /// Seconds for the response times. NaN if there's no data to judge.
+    fn value(&self, res: &ResultData, total_duration: Duration) -> f64 {
+        match *self {
+            Self::Percentile(p) => {
+                res.duration_all_statistics(&StatsConfig {
+                    percentiles: vec![p],
+                    ..Default::default()
+                })
+                .percentiles[0]
+                    .1
+            }
+            Self::Average => res.latency_stat().mean(),
+            Self::Slowest => {
+                let stat = res.latency_stat();
+                // The max of an empty sample is -inf, which would pass
+                if stat.mean().is_nan() {
+                    f64::NAN
+                } else {
+                    stat.max()
+                }
+            }
+            Self::SuccessRate => res.success_rate(),
+            Self::ErrorRate => 1.0 - res.success_rate(),
+            Self::Rps => res.len() as f64 / total_duration.as_secs_f64(),
+        }
+    }
+
+    /// Formats a value of the metric for the text output
+    pub fn format(&self, value: f64) -> String {
+        if self.is_latency() {
+            format!("{value:.4} secs")
+        } else if let Self::SuccessRate | Self::ErrorRate = self {
+            format!("{:.2}%", value * 100.0)
+        } else {
+            format!("{value:.4}")
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "avg" | "average" => Ok(Self::Average),
+            "max" | "slowest" => Ok(Self::Slowest),
+            "success_rate" => Ok(Self::SuccessRate),
+            "error_rate" => Ok(Self::ErrorRate),
+            "rps" => Ok(Self::Rps),
+            _ => s
+                .strip_prefix('p')
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|p| *p > 0.0 && *p <= 100.0)
+                .map(Self::Percentile)
+                .ok_or_else(|| {
+                    format!(
+                        "metric must be one of pN, avg, max, success_rate, error_rate and rps, but got {s}"
+                    )
+                }),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn holds(&self, actual: f64, threshold: f64) -> bool {
+        // NaN fails any of them
+        match self {
+            Self::Lt => actual < threshold,
+            Self::Le => actual <= threshold,
+            Self::Gt => actual > threshold,
+            Self::Ge => actual >= threshold,
+        }
+    }
+}
+
+/// A check on the final results specified by `--assert` like `p99<200ms` or `success_rate>=0.995`
+#[derive(Clone, Debug)]
+pub struct SloAssertion {
+    pub metric: Metric,
+    pub op: Op,
+    /// Seconds for the response times
+    pub threshold: f64,
+    /// As specified
+    pub text: String,
+}
+
+impl FromStr for SloAssertion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let i = s
+            .find(['<', '>'])
+            .ok_or_else(|| format!("assertion must be like p99<200ms, but got {s}"))?;
+        let (metric, rest) = s.split_at(i);
+        let (op, threshold) = if let Some(threshold) = rest.strip_prefix("<=") {
+            (Op::Le, threshold)
+        } else if let Some(threshold) = rest.strip_prefix(">=") {
+            (Op::Ge, threshold)
+        } else if let Some(threshold) = rest.strip_prefix('<') {
+            (Op::Lt, threshold)
+        } else {
+            (Op::Gt, &rest[1..])
+        };
+        let metric = metric.trim().parse::<Metric>()?;
+        let threshold = threshold.trim();
+        let threshold = if metric.is_latency() {
+            humantime::parse_duration(threshold)
+                .map(|d| d.as_secs_f64())
+                .map_err(|err| {
+                    format!("threshold must be a duration like 200ms, but got {threshold}: {err}")
+                })?
+        } else if let Some(percent) = threshold.strip_suffix('%') {
+            percent
+                .trim()
+                .parse::<f64>()
+                .map(|p| p / 100.0)
+                .map_err(|err| format!("invalid threshold {threshold}: {err}"))?
+        } else {
+            threshold
+                .parse::<f64>()
+                .map_err(|err| format!("invalid threshold {threshold}: {err}"))?
+        };
+        Ok(Self {
+            metric,
+            op,
+            threshold,
+            text: s.to_string(),
+        })
+    }
+}
+
+/// The outcome of an `--assert` SLO
+pub struct SloResult {
+    pub assertion: SloAssertion,
+    /// NaN if there's no data to judge, which fails
+    pub actual: f64,
+    pub passed: bool,
+}
+
+/// Checks the SLOs against the final results
+pub fn evaluate(
+    assertions: &[SloAssertion],
+    res: &ResultData,
+    total_duration: Duration,
+) -> Vec<SloResult> {
+    assertions
+        .iter()
+        .map(|assertion| {
+            let actual = assertion.metric.value(res, total_duration);
+            SloResult {
+                assertion: assertion.clone(),
+                actual,
+                passed: assertion.op.holds(actual, assertion.threshold),
+            }
+        })
+        .collect()
+}
+
+/// Exits after the run is interrupted by Ctrl-C, with the same code as finishing with failed SLOs
+pub fn exit_interrupted(
+    assertions: &[SloAssertion],
+    res: &ResultData,
+    total_duration: Duration,
+) -> ! {
+    let failed = evaluate(assertions, res, total_duration)
+        .iter()
+        .filter(|result| !result.passed)
+        .count();
+    if failed > 0 {
+        eprintln!(
+            "Error: {}",
+            SloFailed {
+                failed,
+                total: assertions.len()
+            }
+        );
+        std::process::exit(SloFailed::EXIT_CODE);
+    }
+    std::process::exit(libc::EXIT_SUCCESS);
+}
+
+/// Returned by `run` when any `--assert` SLO failed, to exit with `SloFailed::EXIT_CODE`
+#[derive(thiserror::Error, Debug)]
+#[error("{failed} of {total} --assert SLOs failed")]
+pub struct SloFailed {
+    pub failed: usize,
+    pub total: usize,
+}
+
+impl SloFailed {
+    /// Distinct from 1 for other errors and 2 for invalid arguments
+    pub const EXIT_CODE: i32 = 3;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let assertion: SloAssertion = "p99<200ms".parse().unwrap();
+        assert_eq!(assertion.metric, Metric::Percentile(99.0));
+        assert_eq!(assertion.op, Op::Lt);
+        assert_eq!(assertion.threshold, 0.2);
+        assert_eq!(assertion.text, "p99<200ms");
+
+        let assertion: SloAssertion = "success_rate >= 99.5%".parse().unwrap();
+        assert_eq!(assertion.metric, Metric::SuccessRate);
+        assert_eq!(assertion.op, Op::Ge);
+        assert_eq!(assertion.threshold, 0.995);
+
+        let assertion: SloAssertion = "rps>1000".parse().unwrap();
+        assert_eq!(assertion.metric, Metric::Rps);
+        assert_eq!(assertion.op, Op::Gt);
+        assert_eq!(assertion.threshold, 1000.0);
+
+        let assertion: SloAssertion = "p99.9<=1s".parse().unwrap();
+        assert_eq!(assertion.metric, Metric::Percentile(99.9));
+        assert_eq!(assertion.op, Op::Le);
+
+        assert!("p99=200ms".parse::<SloAssertion>().is_err());
+        assert!("p99<200".parse::<SloAssertion>().is_err());
+        assert!("p101<200ms".parse::<SloAssertion>().is_err());
+        assert!("latency<200ms".parse::<SloAssertion>().is_err());
+        assert!("rps>many".parse::<SloAssertion>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_without_data() {
+        let assertions = [
+            "p99<200ms",
+            "max<1s",
+            "success_rate>=0.995",
+            "error_rate<=1",
+        ]
+        .map(|s| s.parse().unwrap());
+        let results = evaluate(&assertions, &ResultData::default(), Duration::from_secs(1));
+        // Nothing to judge fails
+        assert!(results.iter().all(|r| !r.passed && r.actual.is_nan()));
+    }
+}
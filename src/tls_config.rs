@@ -1,3 +1,33 @@
+/// TLS protocol version which can be selected by `--tls-min` and `--tls-max`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub enum TlsVersion {
+    #[value(name = "1.2")]
+    Tls12,
+    #[value(name = "1.3")]
+    Tls13,
+}
+
+/// TLS session resumption mode which can be selected by `--tls-resumption`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TlsResumption {
+    #[default]
+    On,
+    Off,
+    EarlyData,
+}
+
+/// TLS handshake options shared by all TLS backends
+#[derive(Debug, Clone, Default)]
+pub struct TlsOptions {
+    pub min_version: Option<TlsVersion>,
+    pub max_version: Option<TlsVersion>,
+    /// Allowlist of cipher suites in preference order. Empty means the backend's default.
+    pub cipher_suites: Vec<String>,
+    /// ALPN protocols to offer. None means it's decided by HTTP version.
+    pub alpn: Option<Vec<String>>,
+    pub resumption: TlsResumption,
+}
+
 #[cfg(feature = "rustls")]
 pub struct RuslsConfigs {
     no_alpn: std::sync::Arc<rustls::ClientConfig>,
@@ -10,7 +40,8 @@ impl RuslsConfigs {
         insecure: bool,
         cacert_pem: Option<&[u8]>,
         client_auth: Option<(&[u8], &[u8])>,
-    ) -> Self {
+        options: &TlsOptions,
+    ) -> anyhow::Result<Self> {
         use rustls_pki_types::pem::PemObject;
         use std::sync::Arc;
 
@@ -26,7 +57,49 @@ impl RuslsConfigs {
             }
         }
 
-        let builder = rustls::ClientConfig::builder().with_root_certificates(root_cert_store);
+        let versions = [
+            (TlsVersion::Tls12, &rustls::version::TLS12),
+            (TlsVersion::Tls13, &rustls::version::TLS13),
+        ]
+        .into_iter()
+        .filter(|(v, _)| {
+            options.min_version.is_none_or(|min| *v >= min)
+                && options.max_version.is_none_or(|max| *v <= max)
+        })
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>();
+        anyhow::ensure!(
+            !versions.is_empty(),
+            "--tls-min must not be greater than --tls-max"
+        );
+
+        let mut provider = rustls::ClientConfig::builder()
+            .crypto_provider()
+            .as_ref()
+            .clone();
+        if !options.cipher_suites.is_empty() {
+            let supported = std::mem::take(&mut provider.cipher_suites);
+            for name in &options.cipher_suites {
+                let suite = supported
+                    .iter()
+                    .find(|suite| cipher_suite_matches(suite, name))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown cipher suite: {name}. Supported cipher suites are {}",
+                            supported
+                                .iter()
+                                .filter_map(|suite| suite.suite().as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    })?;
+                provider.cipher_suites.push(*suite);
+            }
+        }
+
+        let builder = rustls::ClientConfig::builder_with_provider(Arc::new(provider))
+            .with_protocol_versions(&versions)?
+            .with_root_certificates(root_cert_store);
 
         let mut config = if let Some((cert, key)) = client_auth {
             let certs = rustls_pki_types::CertificateDer::pem_slice_iter(cert)
@@ -43,15 +116,33 @@ impl RuslsConfigs {
                 .dangerous()
                 .set_certificate_verifier(Arc::new(AcceptAnyServerCert));
         }
+        match options.resumption {
+            TlsResumption::On => {}
+            TlsResumption::Off => config.resumption = rustls::client::Resumption::disabled(),
+            TlsResumption::EarlyData => config.enable_early_data = true,
+        }
 
         let mut no_alpn = config.clone();
         no_alpn.alpn_protocols = vec![];
         let mut alpn_h2 = config;
         alpn_h2.alpn_protocols = vec![b"h2".to_vec()];
-        Self {
+        if let Some(alpn) = &options.alpn {
+            let alpn = alpn
+                .iter()
+                .map(|p| p.as_bytes().to_vec())
+                .collect::<Vec<_>>();
+            no_alpn.alpn_protocols = alpn.clone();
+            alpn_h2.alpn_protocols = alpn;
+        }
+        Ok(Self {
             no_alpn: Arc::new(no_alpn),
             alpn_h2: Arc::new(alpn_h2),
-        }
+        })
+    }
+
+    /// Whether requests may be sent as 0-RTT early data
+    pub fn early_data(&self) -> bool {
+        self.no_alpn.enable_early_data
     }
 
     pub fn config(&self, http: hyper::http::Version) -> &std::sync::Arc<rustls::ClientConfig> {
@@ -64,6 +155,39 @@ impl RuslsConfigs {
     }
 }
 
+/// TLS config to verify DNS over TLS/HTTPS servers with the platform's and `--cacert` roots
+#[cfg(feature = "rustls")]
+pub fn dns_tls_config(
+    cacert_pem: Option<&std::path::Path>,
+) -> anyhow::Result<rustls::ClientConfig> {
+    use rustls_pki_types::pem::PemObject;
+
+    let mut root_cert_store = rustls::RootCertStore::empty();
+    for cert in rustls_native_certs::load_native_certs().certs {
+        root_cert_store.add(cert)?;
+    }
+    if let Some(cacert_pem) = cacert_pem {
+        for der in rustls_pki_types::CertificateDer::pem_file_iter(cacert_pem)? {
+            root_cert_store.add(der?)?;
+        }
+    }
+    Ok(rustls::ClientConfig::builder()
+        .with_root_certificates(root_cert_store)
+        .with_no_client_auth())
+}
+
+/// Matches both rustls' name (e.g. TLS13_AES_128_GCM_SHA256) and IANA's name (e.g. TLS_AES_128_GCM_SHA256)
+#[cfg(feature = "rustls")]
+fn cipher_suite_matches(suite: &rustls::SupportedCipherSuite, name: &str) -> bool {
+    let Some(suite_name) = suite.suite().as_str() else {
+        return false;
+    };
+    suite_name.eq_ignore_ascii_case(name)
+        || suite_name
+            .strip_prefix("TLS13_")
+            .is_some_and(|rest| format!("TLS_{rest}").eq_ignore_ascii_case(name))
+}
+
 #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
 pub struct NativeTlsConnectors {
     pub no_alpn: tokio_native_tls::TlsConnector,
@@ -76,9 +200,36 @@ impl NativeTlsConnectors {
         insecure: bool,
         cacert_pem: Option<&[u8]>,
         client_auth: Option<(&[u8], &[u8])>,
-    ) -> Self {
+        options: &TlsOptions,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            options.cipher_suites.is_empty(),
+            "--tls-ciphers isn't supported with native-tls"
+        );
+        anyhow::ensure!(
+            options.resumption == TlsResumption::On,
+            "--tls-resumption isn't supported with native-tls"
+        );
+        let min_version = match options.min_version {
+            None => None,
+            Some(TlsVersion::Tls12) => Some(native_tls::Protocol::Tlsv12),
+            Some(TlsVersion::Tls13) => {
+                anyhow::bail!("--tls-min 1.3 isn't supported with native-tls")
+            }
+        };
+        let max_version = match options.max_version {
+            Some(TlsVersion::Tls12) => Some(native_tls::Protocol::Tlsv12),
+            // native-tls can't name TLS 1.3, so leave the upper bound to the platform
+            None | Some(TlsVersion::Tls13) => None,
+        };
+
         let new = |is_http2: bool| {
             let mut connector_builder = native_tls::TlsConnector::builder();
+            // Keep native-tls' defaults unless specified
+            if min_version.is_some() {
+                connector_builder.min_protocol_version(min_version);
+            }
+            connector_builder.max_protocol_version(max_version);
 
             if let Some(cacert_pem) = cacert_pem {
                 let cert = native_tls::Certificate::from_pem(cacert_pem)
@@ -98,7 +249,10 @@ impl NativeTlsConnectors {
                 connector_builder.identity(cert);
             }
 
-            if is_http2 {
+            if let Some(alpn) = &options.alpn {
+                connector_builder
+                    .request_alpns(&alpn.iter().map(String::as_str).collect::<Vec<_>>());
+            } else if is_http2 {
                 connector_builder.request_alpns(&["h2"]);
             }
 
@@ -108,10 +262,10 @@ impl NativeTlsConnectors {
                 .into()
         };
 
-        Self {
+        Ok(Self {
             no_alpn: new(false),
             alpn_h2: new(true),
-        }
+        })
     }
 
     pub fn connector(&self, is_http2: bool) -> &tokio_native_tls::TlsConnector {
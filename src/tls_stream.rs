@@ -0,0 +1,92 @@
+use std::{
+    io::IoSlice,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::connection_stats::Connection;
+
+/// Records on the connection whether the TLS handshake resumed a previous session once the handshake completes.
+/// With 0-RTT early data, the stream is returned before that and the handshake completes while requests are sent.
+pub struct TlsStream<S> {
+    // Box for large variant of `Stream`
+    inner: Box<tokio_rustls::client::TlsStream<S>>,
+    /// None once the handshake is recorded
+    connection: Option<Arc<Connection>>,
+}
+
+impl<S> TlsStream<S> {
+    pub fn new(inner: tokio_rustls::client::TlsStream<S>, connection: Arc<Connection>) -> Self {
+        let mut stream = Self {
+            inner: Box::new(inner),
+            connection: Some(connection),
+        };
+        stream.record_handshake();
+        stream
+    }
+
+    fn record_handshake(&mut self) {
+        if self.connection.is_none() {
+            return;
+        }
+        let (_, conn) = self.inner.get_ref();
+        if conn.is_handshaking() {
+            return;
+        }
+        let resumed = conn.handshake_kind() == Some(rustls::HandshakeKind::Resumed);
+        if let Some(connection) = self.connection.take() {
+            connection.tls_handshaken(resumed);
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for TlsStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let poll = Pin::new(&mut *self.inner).poll_read(cx, buf);
+        self.record_handshake();
+        poll
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for TlsStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut *self.inner).poll_write(cx, buf);
+        self.record_handshake();
+        poll
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut *self.inner).poll_write_vectored(cx, bufs);
+        self.record_handshake();
+        poll
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let poll = Pin::new(&mut *self.inner).poll_flush(cx);
+        self.record_handshake();
+        poll
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_shutdown(cx)
+    }
+}
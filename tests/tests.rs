@@ -10,7 +10,12 @@ use std::{
 };
 
 use assert_cmd::Command;
-use axum::{Router, extract::Path, response::Redirect, routing::get};
+use axum::{
+    Router,
+    extract::Path,
+    response::{IntoResponse, Redirect},
+    routing::{get, post},
+};
 use http::{HeaderMap, Request, Response};
 use http_body_util::BodyExt;
 use http_mitm_proxy::MitmProxy;
@@ -963,9 +968,38 @@ async fn test_json_schema() {
     )
     .unwrap();
 
+    let output_json_latency_correction: String = String::from_utf8(
+        tokio::task::spawn_blocking(move || {
+            Command::cargo_bin("oha")
+                .unwrap()
+                .args([
+                    "-n",
+                    "10",
+                    "-q",
+                    "100",
+                    "--latency-correction",
+                    "--no-tui",
+                    "--output-format",
+                    "json",
+                ])
+                .arg(format!("http://127.0.0.1:{port}/"))
+                .assert()
+                .get_output()
+                .stdout
+                .clone()
+        })
+        .await
+        .unwrap(),
+    )
+    .unwrap();
+
     let value: serde_json::Value = serde_json::from_str(&output_json).unwrap();
     let value_stats_success_breakdown: serde_json::Value =
         serde_json::from_str(&output_json_stats_success_breakdown).unwrap();
+    let value_latency_correction: serde_json::Value =
+        serde_json::from_str(&output_json_latency_correction).unwrap();
+    assert!(value.get("latencyCorrection").is_none());
+    assert!(value_latency_correction["latencyCorrection"].is_object());
 
     if validator.validate(&value).is_err() {
         for error in validator.iter_errors(&value) {
@@ -980,6 +1014,13 @@ async fn test_json_schema() {
         }
         panic!("JSON schema validation failed\n{output_json_stats_success_breakdown}");
     }
+
+    if validator.validate(&value_latency_correction).is_err() {
+        for error in validator.iter_errors(&value_latency_correction) {
+            eprintln!("{error}");
+        }
+        panic!("JSON schema validation failed\n{output_json_latency_correction}");
+    }
 }
 
 #[tokio::test]
@@ -1007,20 +1048,20 @@ async fn test_csv_output() {
 
     // Validate that we get CSV output in following format,
     // header and one row for each request:
-    // request-start,DNS,DNS+dialup,Response-delay,request-duration,bytes,status
-    // 0.002211678,0.000374078,0.001148565,0.002619327,0.002626127,11,200
+    // request-start,DNS,DNS+dialup,Response-delay,request-duration,bytes,status,TCP-connect,TLS-handshake,Proxy-CONNECT
+    // 0.002211678,0.000374078,0.001148565,0.002619327,0.002626127,11,200,0.000213371,0,0
     // ...
 
     let lines: Vec<&str> = output_csv.lines().collect();
     assert_eq!(lines.len(), 6);
     assert_eq!(
         lines[0],
-        "request-start,DNS,DNS+dialup,Response-delay,request-duration,bytes,status"
+        "request-start,DNS,DNS+dialup,Response-delay,request-duration,bytes,status,TCP-connect,TLS-handshake,Proxy-CONNECT"
     );
     let mut latest_start = 0f64;
     for line in lines.iter().skip(1) {
         let parts: Vec<&str> = line.split(",").collect();
-        assert_eq!(parts.len(), 7);
+        assert_eq!(parts.len(), 10);
         // validate that the requests are in ascending time order
         let current_start = f64::from_str(parts[0]).unwrap();
         assert!(current_start >= latest_start);
@@ -1031,6 +1072,9 @@ async fn test_csv_output() {
         assert!(f64::from_str(parts[4]).unwrap() > 0f64);
         assert_eq!(usize::from_str(parts[5]).unwrap(), 11);
         assert_eq!(u16::from_str(parts[6]).unwrap(), 200);
+        // Plain HTTP without proxy
+        assert_eq!(f64::from_str(parts[8]).unwrap(), 0f64);
+        assert_eq!(f64::from_str(parts[9]).unwrap(), 0f64);
     }
 }
 
@@ -1123,3 +1167,1203 @@ async fn test_mtls() {
     .await
     .unwrap();
 }
+
+fn setup_tls_server(
+    dir: std::path::PathBuf,
+    protocol_versions: Vec<&'static rustls::SupportedProtocolVersion>,
+) -> (u16, impl Future<Output = Result<(), std::io::Error>>) {
+    let port = PORT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    let app = Router::new().route("/", get(|| async { "Hello, World" }));
+
+    let key_pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_RSA_SHA256).unwrap();
+    let cert = rcgen::CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .self_signed(&key_pair)
+        .unwrap();
+
+    let config = rustls::ServerConfig::builder_with_protocol_versions(&protocol_versions)
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.der().clone()],
+            rustls::pki_types::PrivateKeyDer::Pkcs8(rustls::pki_types::PrivatePkcs8KeyDer::from(
+                key_pair.serialize_der(),
+            )),
+        )
+        .unwrap();
+
+    File::create(dir.join("server.crt"))
+        .unwrap()
+        .write_all(cert.pem().as_bytes())
+        .unwrap();
+
+    let config = axum_server::tls_rustls::RustlsConfig::from_config(Arc::new(config));
+    (
+        port,
+        axum_server::bind_rustls(addr, config).serve(app.into_make_service()),
+    )
+}
+
+async fn debug_succeeds(args: Vec<String>) -> bool {
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .arg("--debug")
+            .args(args)
+            .output()
+            .unwrap()
+            .status
+            .success()
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_tls_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let (port, server) = setup_tls_server(dir.path().to_path_buf(), vec![&rustls::version::TLS13]);
+    tokio::spawn(server);
+
+    let cacert = dir.path().join("server.crt").to_str().unwrap().to_string();
+    let args = |extra: &[&str], url: &str| {
+        let mut args = vec!["--cacert".to_string(), cacert.clone()];
+        args.extend(extra.iter().map(|s| s.to_string()));
+        args.push(url.to_string());
+        args
+    };
+    let localhost = format!("https://localhost:{port}/");
+    let ip = format!("https://127.0.0.1:{port}/");
+
+    assert!(debug_succeeds(args(&[], &localhost)).await);
+    assert!(debug_succeeds(args(&["--tls-min", "1.3"], &localhost)).await);
+    assert!(!debug_succeeds(args(&["--tls-max", "1.2"], &localhost)).await);
+    assert!(!debug_succeeds(args(&["--tls-min", "1.3", "--tls-max", "1.2"], &localhost)).await);
+
+    assert!(
+        debug_succeeds(args(
+            &["--tls-ciphers", "TLS_AES_256_GCM_SHA384"],
+            &localhost
+        ))
+        .await
+    );
+    assert!(!debug_succeeds(args(&["--tls-ciphers", "TLS_NOT_A_CIPHER"], &localhost)).await);
+
+    // The certificate is issued for localhost
+    assert!(!debug_succeeds(args(&[], &ip)).await);
+    assert!(debug_succeeds(args(&["--sni", "localhost"], &ip)).await);
+
+    assert!(debug_succeeds(args(&["--alpn", "http/1.1"], &localhost)).await);
+}
+
+#[tokio::test]
+async fn test_tls_resumption() {
+    let dir = tempfile::tempdir().unwrap();
+    let (port, server) = setup_tls_server(dir.path().to_path_buf(), vec![&rustls::version::TLS13]);
+    tokio::spawn(server);
+
+    let cacert = dir.path().join("server.crt").to_str().unwrap().to_string();
+    let tls_resumption = |resumption: &'static str| {
+        let cacert = cacert.clone();
+        async move {
+            let output = tokio::task::spawn_blocking(move || {
+                Command::cargo_bin("oha")
+                    .unwrap()
+                    .args(["-n", "10", "-c", "1", "--disable-keepalive", "--no-tui"])
+                    .args(["--output-format", "json", "--tls-resumption", resumption])
+                    .args(["--cacert", &cacert])
+                    .arg(format!("https://localhost:{port}/"))
+                    .assert()
+                    .success()
+                    .get_output()
+                    .stdout
+                    .clone()
+            })
+            .await
+            .unwrap();
+            let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+            assert!(
+                value["details"]["TLSHandshake"]["average"]
+                    .as_f64()
+                    .unwrap()
+                    > 0.0
+            );
+            (
+                value["tlsResumption"]["resumed"].as_u64().unwrap(),
+                value["tlsResumption"]["connections"].as_u64().unwrap(),
+            )
+        }
+    };
+
+    let (resumed, connections) = tls_resumption("on").await;
+    assert_eq!(connections, 10);
+    assert!(resumed > 0);
+    assert_eq!(tls_resumption("off").await, (0, 10));
+    // Recorded once the handshake completes after the early data
+    let (resumed, connections) = tls_resumption("early-data").await;
+    assert_eq!(connections, 10);
+    assert!(resumed > 0);
+
+    let localhost = format!("https://localhost:{port}/");
+    let early_data = |method: &str| {
+        vec![
+            "--cacert".to_string(),
+            cacert.clone(),
+            "--tls-resumption".to_string(),
+            "early-data".to_string(),
+            "-m".to_string(),
+            method.to_string(),
+            localhost.clone(),
+        ]
+    };
+    assert!(debug_succeeds(early_data("GET")).await);
+    // 0-RTT data may be replayed
+    assert!(!debug_succeeds(early_data("POST")).await);
+}
+
+#[tokio::test]
+async fn test_connect_timeout() {
+    // Accepts TCP connections but never completes the TLS handshake
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "2", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["--connect-timeout", "100ms", "-t", "10s"])
+            .arg(format!("https://127.0.0.1:{port}/"))
+            .timeout(std::time::Duration::from_secs(5))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["errorDistribution"]["timeout"], 2);
+    assert_eq!(value["errorKinds"]["timeout"]["sample"], "connect timeout");
+}
+
+#[tokio::test]
+async fn test_bind_address() {
+    let (tx, rx) = kanal::unbounded();
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async move {
+        loop {
+            let (tcp, peer) = listener.accept().await.unwrap();
+            tx.send(peer.ip()).unwrap();
+            tokio::spawn(hyper::server::conn::http1::Builder::new().serve_connection(
+                TokioIo::new(tcp),
+                service_fn(|_| async {
+                    Ok::<_, Infallible>(Response::new("Hello World".to_string()))
+                }),
+            ));
+        }
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let hosts_file = dir.path().join("hosts");
+    // Only the IPv4 address can be connected from IPv4 bind addresses
+    std::fs::write(&hosts_file, "::1 dual.test\n127.0.0.1 dual.test\n").unwrap();
+    let hosts_file = hosts_file.to_str().unwrap().to_string();
+
+    let run = move |bind_addresses: &'static [&'static str], host: &'static str| {
+        let hosts_file = hosts_file.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut command = Command::cargo_bin("oha").unwrap();
+            command.args(["-n", "20", "-c", "1", "--disable-keepalive", "--no-tui"]);
+            for bind_address in bind_addresses {
+                command.args(["--bind-address", bind_address]);
+            }
+            command
+                .args(["--output-format", "json", "--hosts-file", &hosts_file])
+                .arg(format!("http://{host}:{port}/"))
+                .output()
+                .unwrap()
+        })
+    };
+
+    let output = run(&["127.0.0.2", "127.0.0.3"], "dual.test").await.unwrap();
+    assert!(output.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["summary"]["successRate"], 1.0);
+    let mut peers = std::collections::BTreeSet::new();
+    while let Ok(Some(peer)) = rx.try_recv() {
+        peers.insert(peer);
+    }
+    assert_eq!(
+        peers,
+        ["127.0.0.2".parse().unwrap(), "127.0.0.3".parse().unwrap()].into()
+    );
+
+    // No address of the same family as the target fails on the lookup before running
+    let output = run(&["::1"], "127.0.0.1").await.unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("No --bind-address has the same address family as 127.0.0.1")
+    );
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args([
+                "-n",
+                "1",
+                "--no-tui",
+                "--ipv6",
+                "--bind-address",
+                "127.0.0.2",
+            ])
+            .arg(format!("http://localhost:{port}/"))
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("--ipv6 requires an IPv6 --bind-address")
+    );
+}
+
+#[tokio::test]
+async fn test_dns_refresh() {
+    let app = Router::new().route("/", get(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    for dns_refresh in ["ttl", "connection", "100ms"] {
+        let output = tokio::task::spawn_blocking(move || {
+            Command::cargo_bin("oha")
+                .unwrap()
+                .args(["-n", "10", "-c", "2", "--disable-keepalive", "--no-tui"])
+                .args(["--output-format", "json", "--dns-refresh", dns_refresh])
+                .arg(format!("http://127.0.0.1:{port}/"))
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone()
+        })
+        .await
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value["remoteIpDistribution"]["127.0.0.1"]["count"], 10);
+        assert_eq!(value["dnsChanges"].as_array().unwrap().len(), 0);
+    }
+
+    assert!(
+        !debug_succeeds(vec![
+            "--dns-refresh".to_string(),
+            "sometimes".to_string(),
+            format!("http://127.0.0.1:{port}/"),
+        ])
+        .await
+    );
+}
+
+/// A DNS server over UDP which answers A queries with what `answer` returns for the n-th A query
+async fn setup_stub_dns_server(
+    answer: impl Fn(usize) -> std::net::Ipv4Addr + Send + 'static,
+) -> u16 {
+    use hickory_resolver::proto::{
+        op::{Message, MessageType},
+        rr::{RData, Record, RecordType, rdata::A},
+    };
+
+    let port = PORT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let socket = tokio::net::UdpSocket::bind(("127.0.0.1", port))
+        .await
+        .unwrap();
+    tokio::spawn(async move {
+        let mut n = 0;
+        let mut buf = [0; 512];
+        loop {
+            let (len, peer) = socket.recv_from(&mut buf).await.unwrap();
+            let req = Message::from_vec(&buf[..len]).unwrap();
+            let mut res = Message::new();
+            res.set_id(req.id())
+                .set_message_type(MessageType::Response)
+                .set_op_code(req.op_code())
+                .set_recursion_desired(req.recursion_desired())
+                .set_recursion_available(true)
+                .add_queries(req.queries().to_vec());
+            for query in req.queries() {
+                if query.query_type() == RecordType::A {
+                    res.add_answer(Record::from_rdata(
+                        query.name().clone(),
+                        0,
+                        RData::A(A(answer(n))),
+                    ));
+                    n += 1;
+                }
+            }
+            socket.send_to(&res.to_vec().unwrap(), peer).await.unwrap();
+        }
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_dns_server() {
+    let app = Router::new().route("/", get(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    // Same port on another loopback address to see traffic move
+    let listener2 = tokio::net::TcpListener::bind(("127.0.0.2", port))
+        .await
+        .unwrap();
+    tokio::spawn(axum::serve(listener, app.clone()).into_future());
+    tokio::spawn(axum::serve(listener2, app).into_future());
+
+    // Cut over to 127.0.0.2 after a few lookups
+    let dns_port = setup_stub_dns_server(|n| {
+        if n < 3 {
+            [127, 0, 0, 1].into()
+        } else {
+            [127, 0, 0, 2].into()
+        }
+    })
+    .await;
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "10", "-c", "1", "--disable-keepalive", "--no-tui"])
+            .args(["--output-format", "json", "--dns-refresh", "connection"])
+            .args(["--dns-server", &format!("127.0.0.1:{dns_port}")])
+            .arg(format!("http://stub.test:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["statusCodeDistribution"]["200"], 10);
+    // The first lookup is done by pre lookup
+    assert_eq!(value["remoteIpDistribution"]["127.0.0.1"]["count"], 2);
+    assert_eq!(value["remoteIpDistribution"]["127.0.0.2"]["count"], 8);
+    assert_eq!(value["dnsChanges"].as_array().unwrap().len(), 1);
+    assert_eq!(value["dnsChangeCount"], 1);
+}
+
+#[tokio::test]
+async fn test_hosts_file() {
+    let app = Router::new().route("/", get(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let dir = tempfile::tempdir().unwrap();
+    let hosts_file = dir.path().join("hosts");
+    std::fs::write(&hosts_file, "127.0.0.1 hosts.test\n").unwrap();
+
+    assert!(
+        debug_succeeds(vec![
+            "--hosts-file".to_string(),
+            hosts_file.to_str().unwrap().to_string(),
+            format!("http://hosts.test:{port}/"),
+        ])
+        .await
+    );
+    // Targets of --connect-to are looked up in the hosts file too
+    assert!(
+        debug_succeeds(vec![
+            "--hosts-file".to_string(),
+            hosts_file.to_str().unwrap().to_string(),
+            "--connect-to".to_string(),
+            format!("example.test:80:hosts.test:{port}"),
+            "http://example.test/".to_string(),
+        ])
+        .await
+    );
+}
+
+#[tokio::test]
+async fn test_decompress() {
+    let body = "Hello World! ".repeat(100);
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(body.as_bytes()).unwrap();
+    let gzip = encoder.finish().unwrap();
+    let gzip_len = gzip.len();
+
+    let app = Router::new()
+        .route(
+            "/gzip",
+            get(move || async move { ([(http::header::CONTENT_ENCODING, "gzip")], gzip) }),
+        )
+        .route(
+            "/broken",
+            get(|| async move { ([(http::header::CONTENT_ENCODING, "gzip")], "Hello World") }),
+        );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args([
+                "-n",
+                "10",
+                "--no-tui",
+                "--output-format",
+                "json",
+                "--decompress",
+            ])
+            .arg(format!("http://127.0.0.1:{port}/gzip"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let gzip = &value["contentEncoding"]["gzip"];
+    assert_eq!(gzip["count"], 10);
+    assert_eq!(gzip["wireBytes"], 10 * gzip_len);
+    assert_eq!(gzip["decodedBytes"], 10 * body.len());
+
+    // Without --decompress only the wire size is known
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "1", "--no-tui", "--output-format", "json"])
+            .arg(format!("http://127.0.0.1:{port}/gzip"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["contentEncoding"]["gzip"]["wireBytes"], gzip_len);
+    assert!(value["contentEncoding"]["gzip"]["decodedBytes"].is_null());
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args([
+                "-n",
+                "1",
+                "--no-tui",
+                "--output-format",
+                "json",
+                "--decompress",
+            ])
+            .arg(format!("http://127.0.0.1:{port}/broken"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let errors = value["errorKinds"].as_object().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors.values().all(|e| {
+        e["sample"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to decode gzip body")
+    }));
+
+    let req = get_req("/", &["--decompress"]).await;
+    assert_eq!(
+        req.headers().get("accept-encoding").unwrap(),
+        "gzip, deflate, br, zstd"
+    );
+}
+
+#[tokio::test]
+async fn test_assertions() {
+    let app = Router::new()
+        .route("/ok", get(|| async move { r#"{"status":"ok"}"# }))
+        .route(
+            "/error",
+            get(|| async move { "<html>Internal error</html>" }),
+        );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let run = |path: &'static str, args: &'static [&'static str]| async move {
+        let output = tokio::task::spawn_blocking(move || {
+            Command::cargo_bin("oha")
+                .unwrap()
+                .args(["-n", "10", "--no-tui", "--output-format", "json"])
+                .args(args)
+                .arg(format!("http://127.0.0.1:{port}{path}"))
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone()
+        })
+        .await
+        .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+    };
+
+    let args: &[&str] = &[
+        "--assert-status",
+        "2xx",
+        "--assert-json",
+        "/status=ok",
+        "--assert-header",
+        "content-type: text/plain; charset=utf-8",
+        "--assert-max-body-size",
+        "100",
+    ];
+    let value = run("/ok", args).await;
+    assert_eq!(value["summary"]["successRate"], 1.0);
+    assert_eq!(value["summary"]["assertionFailures"], 0);
+
+    // A 200 with an error page
+    let value = run("/error", args).await;
+    assert_eq!(value["summary"]["successRate"], 0.0);
+    assert_eq!(value["summary"]["assertionFailures"], 10);
+    assert_eq!(value["errorDistribution"]["validation"], 10);
+    assert_eq!(
+        value["errorKinds"]["validation"]["sample"],
+        "Assertion failed: body isn't JSON"
+    );
+
+    let value = run("/error", &["--assert-body-contains", "\"ok\""]).await;
+    assert_eq!(value["summary"]["assertionFailures"], 10);
+    let value = run("/error", &["--assert-body-regex", "^<html>"]).await;
+    assert_eq!(value["summary"]["assertionFailures"], 0);
+    let value = run("/ok", &["--assert-status", "201,3xx"]).await;
+    assert_eq!(
+        value["errorKinds"]["validation"]["sample"],
+        "Assertion failed: status 200 isn't one of 201,3xx"
+    );
+}
+
+#[tokio::test]
+async fn test_track_header() {
+    let counter = Arc::new(AtomicU16::new(0));
+    let app = Router::new().route(
+        "/",
+        get(move || async move {
+            let n = counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let x_cache = if n < 3 { "MISS" } else { "HIT" };
+            (
+                [("x-cache", x_cache), ("x-served-by", "backend-1")],
+                "Hello World",
+            )
+        }),
+    );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "10", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["--track-header", "X-Cache", "--track-header", "X-Served-By"])
+            .args(["--track-header", "X-Missing"])
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let dist = &value["headerDistribution"];
+    assert_eq!(dist["x-cache"]["MISS"]["count"], 3);
+    assert_eq!(dist["x-cache"]["HIT"]["count"], 7);
+    assert!(dist["x-cache"]["HIT"]["latencyPercentiles"]["p50"].is_number());
+    assert_eq!(dist["x-served-by"]["backend-1"]["count"], 10);
+    assert_eq!(dist["x-missing"]["(missing)"]["count"], 10);
+}
+
+#[tokio::test]
+async fn test_status_code_latency() {
+    let counter = Arc::new(AtomicU16::new(0));
+    let app = Router::new().route(
+        "/",
+        get(move || async move {
+            match counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed) % 3 {
+                0 => http::StatusCode::SERVICE_UNAVAILABLE.into_response(),
+                1 => {
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                    http::StatusCode::OK.into_response()
+                }
+                _ => "Hello World".into_response(),
+            }
+        }),
+    );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "9", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["--stats-success-breakdown", "-t", "100ms"])
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let status_code_latency = value["statusCodeLatency"].as_object().unwrap();
+    assert_eq!(status_code_latency.len(), 2);
+    assert!(status_code_latency["503"]["percentiles"]["p50"].is_number());
+    assert!(status_code_latency["200"]["histogram"].is_object());
+    let time_to_failure = value["errorTimeToFailure"]["timeout"]["percentiles"]["p50"]
+        .as_f64()
+        .unwrap();
+    assert!(time_to_failure >= 0.1);
+}
+
+#[tokio::test]
+async fn test_url_distribution() {
+    let app = Router::new()
+        .route("/ok", get(|| async move { "Hello World" }))
+        .route(
+            "/fail/{n}",
+            get(|| async move { http::StatusCode::INTERNAL_SERVER_ERROR }),
+        );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "50", "--no-tui", "--output-format", "json"])
+            .args(["--rand-regex-url", "--url-group", "/fail/"])
+            .arg(format!("http://127.0.0.1:{port}/(ok|fail/[0-9])"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let dist = value["urlDistribution"].as_object().unwrap();
+    assert_eq!(dist.len(), 2);
+    let ok = &dist[&format!("http://127.0.0.1:{port}/ok")];
+    let fail = &dist["/fail/"];
+    assert_eq!(
+        ok["count"].as_u64().unwrap() + fail["count"].as_u64().unwrap(),
+        50
+    );
+    assert_eq!(ok["errorRate"], 0.0);
+    assert_eq!(fail["errorRate"], 1.0);
+    assert!(fail["latencyPercentiles"]["p99"].is_number());
+}
+
+#[tokio::test]
+async fn test_retries() {
+    let counter = Arc::new(AtomicU16::new(0));
+    let app = Router::new().route(
+        "/",
+        get(move || async move {
+            // Every other request fails
+            if counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed) % 2 == 0 {
+                http::StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                http::StatusCode::OK
+            }
+        }),
+    );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "5", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["--retries", "2", "--retry-backoff", "1ms"])
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["statusCodeDistribution"]["200"], 5);
+    assert_eq!(value["retries"]["distribution"]["1"], 5);
+    assert_eq!(value["retries"]["firstAttemptSuccessRate"], 0.0);
+    assert_eq!(value["summary"]["successRate"], 1.0);
+
+    // Only 502 is retried
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "4", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["--retries", "2", "--retry-on", "502"])
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["statusCodeDistribution"]["503"], 2);
+    assert_eq!(value["retries"]["distribution"]["0"], 4);
+}
+
+/// Serves HTTP/2 and kills the first connection at the 3rd request on it
+async fn bind_http2_killing_first_connection() -> u16 {
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async move {
+        let mut first = true;
+        loop {
+            let (tcp, _) = listener.accept().await.unwrap();
+            // The first connection is killed at the 3rd request on it
+            let kill_at = if first { 3 } else { usize::MAX };
+            first = false;
+            let (kill_tx, kill_rx) = kanal::unbounded_async::<()>();
+            let requests = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            tokio::spawn(async move {
+                let conn = hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                    .serve_connection(
+                        TokioIo::new(tcp),
+                        service_fn(move |_| {
+                            let kill_tx = kill_tx.clone();
+                            let n = requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                            async move {
+                                if n == kill_at {
+                                    let _ = kill_tx.send(()).await;
+                                    std::future::pending::<()>().await;
+                                }
+                                Ok::<_, Infallible>(Response::new("Hello World".to_string()))
+                            }
+                        }),
+                    );
+                // Dropping the connection closes the socket with the request in flight
+                tokio::select! {
+                    _ = conn => {}
+                    _ = kill_rx.recv() => {}
+                }
+            });
+        }
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_http2_retry_reconnect() {
+    let port = bind_http2_killing_first_connection().await;
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "10", "-c", "1", "-p", "1", "--http2", "--no-tui"])
+            .args(["--output-format", "json"])
+            .args(["--retries", "1", "--retry-backoff", "1ms"])
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["summary"]["successRate"], 1.0);
+    assert_eq!(value["statusCodeDistribution"]["200"], 10);
+    assert_eq!(value["retries"]["distribution"]["0"], 9);
+    assert_eq!(value["retries"]["distribution"]["1"], 1);
+}
+
+#[tokio::test]
+async fn test_http2_retry_reconnect_parallel() {
+    let port = bind_http2_killing_first_connection().await;
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "20", "-c", "1", "-p", "4", "--http2", "--no-tui"])
+            .args(["--output-format", "json"])
+            .args(["--retries", "1", "--retry-backoff", "1ms"])
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(value["summary"]["successRate"], 1.0);
+    assert_eq!(value["statusCodeDistribution"]["200"], 20);
+    assert!(value["retries"]["distribution"]["1"].as_u64().unwrap() >= 1);
+    // The retries of the parallel requests share one new connection
+    assert_eq!(value["connections"]["connections"], 2);
+    assert_eq!(value["connections"]["reconnects"], 1);
+}
+
+#[tokio::test]
+async fn test_connections() {
+    let app = Router::new()
+        .route("/", get(|| async move { "Hello World" }))
+        .route(
+            "/close",
+            get(|| async move { ([(http::header::CONNECTION, "close")], "Hello World") }),
+        );
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let run = |path: &'static str, args: &'static [&'static str]| {
+        tokio::task::spawn_blocking(move || {
+            let output = Command::cargo_bin("oha")
+                .unwrap()
+                .args(["-n", "10", "-c", "1", "--no-tui", "--output-format", "json"])
+                .args(args)
+                .arg(format!("http://127.0.0.1:{port}{path}"))
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone();
+            serde_json::from_slice::<serde_json::Value>(&output).unwrap()["connections"].clone()
+        })
+    };
+
+    let keep_alive = run("/", &[]).await.unwrap();
+    assert_eq!(keep_alive["connections"], 1);
+    assert_eq!(keep_alive["reconnects"], 0);
+    assert_eq!(keep_alive["requestsPerConnection"]["10"], 1);
+    assert_eq!(keep_alive["requestsPerConnectionPercentiles"]["p50"], 10);
+    assert!(keep_alive["lifetime"]["slowest"].as_f64().unwrap() > 0.0);
+
+    let close = run("/close", &[]).await.unwrap();
+    assert_eq!(close["connections"], 10);
+    assert_eq!(close["reconnects"], 9);
+    assert_eq!(close["requestsPerConnection"]["1"], 10);
+
+    // Responses which fail the assertions are served too
+    let assert_failed = run("/", &["--assert-status", "201"]).await.unwrap();
+    assert_eq!(assert_failed["connections"], 1);
+    assert_eq!(assert_failed["requestsPerConnection"]["10"], 1);
+    assert!(assert_failed["lifetime"]["slowest"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn test_throughput() {
+    let app = Router::new().route("/", post(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "10", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["-m", "POST", "-d", &"a".repeat(1000)])
+            .arg(format!("http://127.0.0.1:{port}"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let throughput = &value["throughput"];
+    // Averages of the requests, as the text output
+    let request_size = throughput["requestSize"].as_u64().unwrap();
+    assert!(request_size > 1000);
+    // HTTP/1 on the wire is the same as the estimate except for headers added by hyper
+    assert!(throughput["bytesSent"].as_u64().unwrap() >= 10 * request_size);
+    let response_header_size = throughput["responseHeaderSize"].as_u64().unwrap();
+    assert!(response_header_size > "HTTP/1.1 200 OK\r\n".len() as u64);
+    assert_eq!(
+        throughput["bytesReceived"].as_u64().unwrap(),
+        10 * (response_header_size + "Hello World".len() as u64)
+    );
+    assert!(throughput["sentPerSec"].as_f64().unwrap() > 0.0);
+    assert_eq!(throughput["responseSizePercentiles"]["p50"], 11);
+}
+
+#[tokio::test]
+async fn test_slo_assertions() {
+    let app = Router::new().route("/", get(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let run = |slo: &'static [&'static str]| {
+        tokio::task::spawn_blocking(move || {
+            let mut command = Command::cargo_bin("oha").unwrap();
+            command.args(["-n", "10", "--no-tui", "--output-format", "json"]);
+            for slo in slo {
+                command.args(["--assert", slo]);
+            }
+            let output = command
+                .arg(format!("http://127.0.0.1:{port}"))
+                .output()
+                .unwrap();
+            (
+                output.status.code(),
+                serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap(),
+            )
+        })
+    };
+
+    let (code, value) = run(&["p99<10s", "success_rate>=99.5%"]).await.unwrap();
+    assert_eq!(code, Some(0));
+    assert_eq!(value["sloAssertions"][0]["assertion"], "p99<10s");
+    assert_eq!(value["sloAssertions"][0]["passed"], true);
+    assert_eq!(value["sloAssertions"][1]["actual"], 1.0);
+
+    let (code, value) = run(&["success_rate>=0.995", "rps>1e12"]).await.unwrap();
+    assert_eq!(code, Some(3));
+    assert_eq!(value["sloAssertions"][0]["passed"], true);
+    assert_eq!(value["sloAssertions"][1]["passed"], false);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_slo_assertions_ctrl_c() {
+    let app = Router::new().route("/", get(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let run = |slo: &'static str| {
+        tokio::task::spawn_blocking(move || {
+            let child = std::process::Command::new(env!("CARGO_BIN_EXE_oha"))
+                .args(["-z", "10s", "--no-tui", "--output-format", "json"])
+                .args(["--assert", slo])
+                .arg(format!("http://127.0.0.1:{port}"))
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .unwrap();
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            unsafe {
+                libc::kill(child.id() as libc::pid_t, libc::SIGINT);
+            }
+            let output = child.wait_with_output().unwrap();
+            (
+                output.status.code(),
+                serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap(),
+            )
+        })
+    };
+
+    let (code, value) = run("success_rate>=0.5").await.unwrap();
+    assert_eq!(code, Some(0));
+    assert_eq!(value["sloAssertions"][0]["passed"], true);
+
+    let (code, value) = run("rps>1e12").await.unwrap();
+    assert_eq!(code, Some(3));
+    assert_eq!(value["sloAssertions"][0]["passed"], false);
+}
+
+#[tokio::test]
+async fn test_percentiles_and_histogram_boundaries() {
+    let app = Router::new().route("/", get(|| async move { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "10", "--no-tui", "--output-format", "json"])
+            .args(["--percentiles", "99.999,50"])
+            .args(["--histogram-boundaries", "10s,1ns"])
+            .arg(format!("http://127.0.0.1:{port}"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let percentiles = value["latencyPercentiles"].as_object().unwrap();
+    assert_eq!(
+        percentiles.keys().collect::<Vec<_>>(),
+        vec!["p50", "p99.999"]
+    );
+    let histogram = value["responseTimeHistogram"].as_object().unwrap();
+    assert_eq!(histogram.len(), 2);
+    assert_eq!(histogram["0.000000001"], 0);
+    assert_eq!(histogram["10"], 10);
+}
+
+#[tokio::test]
+async fn test_redirect_hops() {
+    let app = Router::new()
+        .route("/login", post(|| async { Redirect::to("/session") }))
+        .route("/session", get(|| async { Redirect::temporary("/home") }))
+        // 307 keeps the method
+        .route("/home", post(|| async { "home" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let output = tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "3", "-c", "1", "--no-tui", "--output-format", "json"])
+            .args(["-m", "POST", "-d", "user=a"])
+            .arg(format!("http://127.0.0.1:{port}/login"))
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    })
+    .await
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    // POST /login -> 303 GET /session -> 307 GET /home, which only accepts POST
+    assert_eq!(value["statusCodeDistribution"]["405"], 3);
+    assert_eq!(value["redirects"]["hopDistribution"]["2"], 3);
+    let hops = value["redirects"]["hops"].as_array().unwrap();
+    assert_eq!(hops.len(), 2);
+    assert_eq!(hops[0]["statusCodeDistribution"]["303"], 3);
+    assert_eq!(hops[1]["statusCodeDistribution"]["307"], 3);
+}
+
+#[cfg(all(unix, feature = "rustls"))]
+#[tokio::test]
+async fn test_unix_socket_https() {
+    let (tx, rx) = kanal::unbounded();
+
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("socket");
+
+    let key_pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_RSA_SHA256).unwrap();
+    let cert = rcgen::CertificateParams::new(vec!["localhost".to_string()])
+        .unwrap()
+        .self_signed(&key_pair)
+        .unwrap();
+    let cacert = tmp.path().join("server.crt");
+    File::create(&cacert)
+        .unwrap()
+        .write_all(cert.pem().as_bytes())
+        .unwrap();
+    let config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![cert.der().clone()],
+            rustls::pki_types::PrivateKeyDer::Pkcs8(rustls::pki_types::PrivatePkcs8KeyDer::from(
+                key_pair.serialize_der(),
+            )),
+        )
+        .unwrap();
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let stream = acceptor.accept(stream).await.unwrap();
+            let sni = stream.get_ref().1.server_name().map(|s| s.to_string());
+            let tx = tx.clone();
+            tokio::spawn(hyper::server::conn::http1::Builder::new().serve_connection(
+                TokioIo::new(stream),
+                service_fn(move |_req| {
+                    tx.send(sni.clone()).unwrap();
+                    async { Ok::<_, Infallible>(Response::new("Hello World".to_string())) }
+                }),
+            ));
+        }
+    });
+
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args(["-n", "1", "--no-tui", "--cacert"])
+            .arg(&cacert)
+            .arg("--unix-socket")
+            .arg(&path)
+            .arg("https://localhost/")
+            .assert()
+            .success();
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        rx.try_recv().unwrap().unwrap().as_deref(),
+        Some("localhost")
+    );
+}
+
+#[tokio::test]
+async fn test_report_interval() {
+    let app = Router::new().route("/", get(|| async { "Hello World" }));
+    let (listener, port) = bind_port().await;
+    tokio::spawn(async { axum::serve(listener, app).await });
+
+    let dir = tempfile::tempdir().unwrap();
+    let report_file = dir.path().join("report.jsonl");
+    let report_file_clone = report_file.clone();
+    tokio::task::spawn_blocking(move || {
+        Command::cargo_bin("oha")
+            .unwrap()
+            .args([
+                "-z",
+                "1100ms",
+                "-c",
+                "1",
+                "--no-tui",
+                "--output-format",
+                "json",
+            ])
+            .args(["--report-interval", "500ms", "--report-file"])
+            .arg(&report_file_clone)
+            .arg(format!("http://127.0.0.1:{port}/"))
+            .assert()
+            .success();
+    })
+    .await
+    .unwrap();
+
+    let reports = std::fs::read_to_string(report_file).unwrap();
+    let reports = reports
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    // 0.5s, 1s and the rest
+    assert_eq!(reports.len(), 3);
+    assert!(reports[0]["requests"].as_u64().unwrap() > 0);
+    assert_eq!(
+        reports[0]["requests"],
+        reports[0]["statusCodeDistribution"]["200"]
+    );
+    assert!(reports[1]["latencyPercentiles"]["p99"].is_number());
+    assert!(reports[1]["elapsed"].as_f64().unwrap() > 0.9);
+}